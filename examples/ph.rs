@@ -1,6 +1,6 @@
-extern crate unit_maths;
+extern crate pcm_calc;
 
-use unit_maths::*;
+use pcm_calc::*;
 
 macro_rules! print_eval {
     ($eval:expr) => (
@@ -1,8 +1,8 @@
-extern crate unit_maths;
+extern crate pcm_calc;
 
 use std::io::stdin;
 
-use unit_maths::*;
+use pcm_calc::*;
 
 fn main() {
     let si = UnitSystem::<f64>::si();
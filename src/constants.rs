@@ -0,0 +1,101 @@
+//! Physical constants with their CODATA 2018 values
+
+use super::*;
+
+use num::Float;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+/// A physical constant
+pub struct Constant {
+    /// The symbol it's registered by in a `UnitSystem`, e.g. `"k_B"`
+    pub symbol: &'static str,
+    /// The name of the constant
+    pub name: &'static str,
+    /// The value in SI base units
+    pub value: f64,
+    /// The standard uncertainty in SI base units, which is zero for exact constants
+    pub uncertainty: f64,
+    /// The dimension of the constant
+    pub dimension: Dimension,
+}
+
+impl Constant {
+    /// The value of the constant
+    pub fn value<N: Float>(&self) -> Value<N> {
+        Value(N::from(self.value).unwrap(), Unit::new(self.dimension))
+    }
+    /// The value of the constant with its standard uncertainty
+    ///
+    /// Every call makes a new independent measurement,
+    /// so clone the result to use the same one in several places
+    pub fn uncertain<N: Float>(&self) -> Uncertain<N> {
+        Uncertain::new(self.value(), N::from(self.uncertainty).unwrap())
+    }
+    /// Whether the value is exact by the definition of the SI
+    pub fn is_exact(&self) -> bool {
+        self.uncertainty == 0.
+    }
+}
+
+macro_rules! constants {
+    ($($cnst:ident, $symbol:expr, $name:expr, $value:expr, $uncertainty:expr; {$($n:ident : $v:expr),+},)*) => (
+        $(
+            #[doc = $name]
+            pub const $cnst: Constant = Constant {
+                symbol: $symbol,
+                name: $name,
+                value: $value,
+                uncertainty: $uncertainty,
+                dimension: Dimension{$($n: Exponent::integer($v),)+..NUL},
+            };
+        )*
+        /// All the constants of this module
+        pub const CODATA_2018: &[Constant] = &[$($cnst),*];
+    );
+}
+
+/// Other symbols constants are registered by, mapped to the symbols of the constants
+///
+/// The symbol `h` of Planck's constant is also the hour, which is read first
+/// since units come before constants, so Planck's constant is read as `h_P` or `ℎ` instead
+pub const ALIASES: &[(&str, &str)] = &[("h_P", "h"), ("ℎ", "h")];
+
+constants!{
+    SPEED_OF_LIGHT, "c", "Speed of light in vacuum", 299792458., 0.; {length:1, time:-1},
+    PLANCK, "h", "Planck constant", 6.62607015e-34, 0.; {mass:1, length:2, time:-1},
+    REDUCED_PLANCK, "ħ", "Reduced Planck constant", 1.0545718176461565e-34, 0.; {mass:1, length:2, time:-1},
+    BOLTZMANN, "k_B", "Boltzmann constant", 1.380649e-23, 0.; {mass:1, length:2, time:-2, temperature:-1},
+    AVOGADRO, "N_A", "Avogadro constant", 6.02214076e23, 0.; {substance_amount:-1},
+    ELEMENTARY_CHARGE, "e", "Elementary charge", 1.602176634e-19, 0.; {current:1, time:1},
+    GRAVITATIONAL, "G", "Newtonian constant of gravitation", 6.67430e-11, 0.00015e-11; {length:3, mass:-1, time:-2},
+    VACUUM_PERMITTIVITY, "ε₀", "Vacuum electric permittivity", 8.8541878128e-12, 0.0000000013e-12; {mass:-1, length:-3, time:4, current:2},
+    VACUUM_PERMEABILITY, "μ₀", "Vacuum magnetic permeability", 1.25663706212e-6, 0.00000000019e-6; {mass:1, length:1, time:-2, current:-2},
+    MOLAR_GAS, "R", "Molar gas constant", 8.314462618, 0.; {mass:1, length:2, time:-2, temperature:-1, substance_amount:-1},
+    ELECTRON_MASS, "mₑ", "Electron mass", 9.1093837015e-31, 0.0000000028e-31; {mass:1},
+}
+
+#[test]
+fn constants_test() {
+    let mut si = UnitSystem::<f64>::si();
+    si.add_constants();
+
+    assert_eq!(SPEED_OF_LIGHT.value(), si.val(299792458., "m/s").unwrap());
+    assert_eq!(BOLTZMANN.value::<f64>().1, si.unit_from_str("J/K").unwrap());
+    assert_eq!(MOLAR_GAS.dimension, si.unit_from_str("k_B N_A").unwrap().dimension);
+    assert!((REDUCED_PLANCK.value - PLANCK.value / (2. * std::f64::consts::PI)).abs() < 1e-48);
+    assert!(ELECTRON_MASS.uncertain::<f64>().uncertainty() > 0.);
+    assert!(PLANCK.is_exact());
+
+    let one = si.val_s("1 ε₀ μ₀ c²").unwrap();
+    assert_eq!(one.1.dimension, NUL);
+    assert!((one.1.factor - 1.).abs() < 1e-9);
+    assert_eq!(si.val_s("2 h_P").unwrap().1, Unit::with_factor(PLANCK.dimension, PLANCK.value));
+    assert_eq!(si.val_s("2 ℎ").unwrap().1, Unit::with_factor(PLANCK.dimension, PLANCK.value));
+    assert_eq!(si.constants.get("h"), Some(&Unit::with_factor(PLANCK.dimension, PLANCK.value)));
+    assert_eq!(si.get_unit("h"), Some(Unit::with_factor(TIME, 3600.)));
+    assert!(!si.display(&si.val_s("1 km/h").unwrap()).to_string().ends_with(" c"));
+
+    let g_n = String::from("g_n");
+    si.add_constant(g_n, Unit::with_factor(ACCELERATION, 9.80665));
+    assert_eq!(si.val_s("2 g_n").unwrap().1, Unit::with_factor(ACCELERATION, 9.80665));
+}
@@ -0,0 +1,155 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use num::Float;
+
+/// The line number and right-hand side of each definition by name
+type Pending<'a> = HashMap<&'a str, (usize, &'a str)>;
+
+impl<N: Float + FromStr> UnitSystem<N> {
+    /// Adds the units defined in `defs`
+    ///
+    /// Each line is empty, a comment starting with `#` or a definition like `furlong = 201.168 m`
+    /// or `therm = 105.5 MJ`. The right-hand side is read like `unit_from_str` and may use units
+    /// defined anywhere in `defs`. Units already readable by the system, including prefixed units
+    /// like `kWh`, may be defined again if the definition gives the same unit.
+    ///
+    /// Lines like `dimension information` declaring new base dimensions are not supported.
+    /// A `Dimension` has a fixed set of base dimensions, so they fail with
+    /// `DefinitionError::NewDimension` rather than making units that mix with dimensionless ones.
+    ///
+    /// Fails without adding anything on the first line that can't be read, on names defined twice
+    /// or already readable by the system as another unit, and on definitions depending on each
+    /// other in a cycle
+    pub fn load_definitions(&mut self, defs: &str) -> Result<(), DefinitionError> {
+        let mut pending = Pending::new();
+        let mut redefined = Vec::new();
+        for (i, text) in defs.lines().enumerate() {
+            let line = i + 1;
+            let text = text.split('#').next().unwrap().trim();
+            if text.is_empty() {
+                continue
+            }
+            if let Some(name) = text.strip_prefix("dimension ").filter(|_| !text.contains('=')) {
+                return Err(DefinitionError::NewDimension {line, name: name.trim().to_owned()})
+            }
+            let (name, rhs) = match text.find('=') {
+                Some(eq) => (text[..eq].trim(), text[eq+1..].trim()),
+                None => ("", ""),
+            };
+            if name.is_empty() || rhs.is_empty() || !name.chars().all(is_unit_char) {
+                return Err(DefinitionError::Syntax {line, text: text.to_owned()})
+            }
+            if pending.contains_key(name) || redefined.iter().any(|&(_, n, _)| n == name) {
+                return Err(DefinitionError::Duplicate {line, name: name.to_owned()})
+            }
+            if self.get_unit(name).is_some() {
+                redefined.push((line, name, rhs));
+            } else {
+                pending.insert(name, (line, rhs));
+            }
+        }
+
+        let mut added = Vec::new();
+        let mut names: Vec<_> = pending.keys().cloned().collect();
+        names.sort_by_key(|name| pending[name].0);
+        let mut result = names.into_iter()
+            .try_for_each(|name| self.resolve(name, &pending, &mut Vec::new(), &mut added));
+        if result.is_ok() {
+            result = redefined.into_iter().try_for_each(|(line, name, rhs)| {
+                let unit = unit_from_str(self, rhs).map_err(|error| DefinitionError::Parse {line, error})?;
+                match self.get_unit(name) {
+                    Some(old) if same_unit(&old, &unit) => Ok(()),
+                    _ => Err(DefinitionError::Duplicate {line, name: name.to_owned()}),
+                }
+            });
+        }
+        if result.is_err() {
+            for name in added {
+                self.units.remove(name);
+            }
+        }
+        result
+    }
+    /// Adds the definition of `name` after the definitions it depends on
+    ///
+    /// `stack` holds the definitions waiting for this one, and `added` gets the names added
+    fn resolve<'a>(&mut self, name: &'a str, pending: &Pending<'a>, stack: &mut Vec<&'a str>, added: &mut Vec<&'a str>)
+    -> Result<(), DefinitionError> {
+        if self.units.contains_key(name) {
+            return Ok(())
+        }
+        let (line, rhs) = pending[name];
+        stack.push(name);
+        loop {
+            let dependency = match unit_from_str(self, rhs) {
+                Ok(unit) => {
+                    self.add_unit(name.to_owned(), unit);
+                    added.push(name);
+                    stack.pop();
+                    return Ok(())
+                }
+                Err(ParseError::UnknownUnit{ref name, ..}) if pending.contains_key(name.as_str()) => {
+                    *pending.keys().find(|&n| n == name).unwrap()
+                }
+                Err(error) => return Err(DefinitionError::Parse {line, error}),
+            };
+            if let Some(start) = stack.iter().position(|&n| n == dependency) {
+                let mut names: Vec<_> = stack[start..].iter().map(|&n| n.to_owned()).collect();
+                names.push(dependency.to_owned());
+                return Err(DefinitionError::Cycle {line: pending[dependency].0, names})
+            }
+            self.resolve(dependency, pending, stack, added)?;
+        }
+    }
+    /// Creates a system with the SI units and the units defined in the file at `path`
+    ///
+    /// See `load_definitions` for the format of the file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DefinitionError> {
+        let defs = fs::read_to_string(path).map_err(|e| DefinitionError::Io(e.to_string()))?;
+        let mut sys = Self::si();
+        sys.load_definitions(&defs)?;
+        Ok(sys)
+    }
+}
+
+/// Whether the units are the same, up to rounding errors in the factors
+fn same_unit<N: Float>(a: &Unit<N>, b: &Unit<N>) -> bool {
+    let tolerance = N::epsilon() * (N::one() + N::one() + N::one() + N::one());
+    a.dimension == b.dimension && a.offset == b.offset && a.log == b.log
+        && (a.factor - b.factor).abs() <= tolerance * a.factor.abs().max(b.factor.abs())
+}
+
+#[test]
+fn definitions_test() {
+    let mut si = UnitSystem::<f64>::si();
+    si.load_definitions("
+        # Lengths
+        furlong = 220 yard   # Defined before the yard
+        yard = 3 foot
+        foot = 0.3048 m
+        therm = 105.5 MJ
+    ").unwrap();
+    assert!((si.get_unit("furlong").unwrap().factor - 201.168).abs() < 1e-9);
+    assert_eq!(si.get_unit("therm"), Some(Unit::with_factor(ENERGY, 105.5e6)));
+    si.load_definitions("kWh = 3.6 MJ\nh = 60 min").unwrap();
+    assert_eq!(si.load_definitions("kWh = 3.7 MJ").unwrap_err(), DefinitionError::Duplicate{line: 1, name: "kWh".to_owned()});
+
+    let mut si = UnitSystem::<f64>::si();
+    let err = si.load_definitions("a = 2 b\n\nb = 3 c\nc = a/s\nd = 1 m").unwrap_err();
+    assert_eq!(err, DefinitionError::Cycle{line: 1, names: vec!["a".to_owned(), "b".to_owned(), "c".to_owned(), "a".to_owned()]});
+    assert_eq!(si.get_unit("b"), None);
+    assert_eq!(si.get_unit("d"), None);
+
+    assert_eq!(si.load_definitions("x = 1 m\nx = 2 m").unwrap_err(), DefinitionError::Duplicate{line: 2, name: "x".to_owned()});
+    assert_eq!(si.load_definitions("\nmin = 61 s").unwrap_err().line(), Some(2));
+    assert_eq!(si.load_definitions("x = 1 m\ny 2 m").unwrap_err().to_string(), "Line 2: Expected a definition like `name = value`, found `y 2 m'");
+    assert_eq!(si.load_definitions("x = 2 mx").unwrap_err().line(), Some(1));
+    assert_eq!(si.load_definitions("byte = 8 bit\ndimension bit").unwrap_err(), DefinitionError::NewDimension{line: 2, name: "bit".to_owned()});
+    assert_eq!(si.get_unit("byte"), None);
+    assert!(UnitSystem::<f64>::from_file("/nonexistent/units.txt").is_err());
+}
@@ -1,42 +1,291 @@
 use super::*;
 
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, Mul, Neg, Sub};
 use std::fmt::{self, Display};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use num::{Float, Integer};
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+/// A rational exponent of a base dimension, like the ½ in V/√Hz
+///
+/// It is always kept in lowest terms with a positive denominator,
+/// so equal exponents compare and hash equal.
+pub struct Exponent {
+    numer: i16,
+    denom: i16,
+}
+
+impl Exponent {
+    /// The exponent zero
+    pub const ZERO: Exponent = Exponent::integer(0);
+    /// The exponent one
+    pub const ONE: Exponent = Exponent::integer(1);
+
+    /// Makes an integer exponent
+    pub const fn integer(n: i16) -> Self {
+        Exponent{numer: n, denom: 1}
+    }
+    /// Makes the exponent `numer/denom`
+    ///
+    /// # Panics
+    ///
+    /// If `denom` is zero or the reduced fraction doesn't fit in an `i16`
+    pub fn new(numer: i16, denom: i16) -> Self {
+        Exponent::reduced(numer as i32, denom as i32)
+    }
+    /// Makes the exponent `numer/denom`, returning `None` if `denom` is zero
+    /// or the reduced fraction doesn't fit in an `i16`
+    pub fn checked_new(numer: i16, denom: i16) -> Option<Self> {
+        Exponent::checked_reduced(numer as i64, denom as i64)
+    }
+    fn reduced(numer: i32, denom: i32) -> Self {
+        assert!(denom != 0, "exponent with denominator zero");
+        Exponent::checked_reduced(numer as i64, denom as i64).expect("exponent overflow")
+    }
+    /// The fraction `numer/denom` in lowest terms, if the denominator isn't zero and it fits in an `i16`
+    fn checked_reduced(numer: i64, denom: i64) -> Option<Self> {
+        if denom == 0 {
+            return None
+        }
+        let gcd = numer.gcd(&denom) * denom.signum();
+        let (numer, denom) = (numer / gcd, denom / gcd);
+        if numer.abs() > i16::MAX as i64 || denom > i16::MAX as i64 {
+            return None
+        }
+        Some(Exponent{numer: numer as i16, denom: denom as i16})
+    }
+    /// Adds two exponents, returning `None` if the result doesn't fit
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Exponent::checked_reduced(
+            self.numer as i64 * rhs.denom as i64 + rhs.numer as i64 * self.denom as i64,
+            self.denom as i64 * rhs.denom as i64,
+        )
+    }
+    /// Subtracts two exponents, returning `None` if the result doesn't fit
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(-rhs)
+    }
+    /// Multiplies two exponents, returning `None` if the result doesn't fit
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Exponent::checked_reduced(self.numer as i64 * rhs.numer as i64, self.denom as i64 * rhs.denom as i64)
+    }
+    /// The numerator
+    pub fn numer(self) -> i16 {
+        self.numer
+    }
+    /// The denominator, which is always positive
+    pub fn denom(self) -> i16 {
+        self.denom
+    }
+    /// Whether the exponent is a whole number
+    pub fn is_integer(self) -> bool {
+        self.denom == 1
+    }
+    /// The absolute value
+    pub fn abs(self) -> Self {
+        Exponent{numer: self.numer.abs(), ..self}
+    }
+    /// The exponent equal to `x`, if it's a fraction with a denominator of at most 12
+    pub fn approximate<N: Float>(x: N) -> Option<Self> {
+        let x = x.to_f64()?;
+        (1..13).map(|d| (x * d as f64, d))
+            .find(|&(n, _)| (n - n.round()).abs() < 1e-9 && n.abs() <= i16::MAX as f64)
+            .map(|(n, d)| Exponent::new(n.round() as i16, d))
+    }
+    /// Raises `n` to this power
+    pub fn pow<N: Float>(self, n: N) -> N {
+        if self.is_integer() {
+            n.powi(self.numer as i32)
+        } else {
+            n.powf(N::from(self.numer).unwrap() / N::from(self.denom).unwrap())
+        }
+    }
+}
+
+impl Default for Exponent {
+    fn default() -> Self {
+        Exponent::ZERO
+    }
+}
+
+impl From<i16> for Exponent {
+    fn from(n: i16) -> Self {
+        Exponent::integer(n)
+    }
+}
+
+impl PartialEq<i16> for Exponent {
+    fn eq(&self, other: &i16) -> bool {
+        *self == Exponent::integer(*other)
+    }
+}
+
+impl Ord for Exponent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numer as i32 * other.denom as i32).cmp(&(other.numer as i32 * self.denom as i32))
+    }
+}
+
+impl PartialOrd for Exponent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Exponent {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).expect("exponent overflow")
+    }
+}
+
+impl Sub for Exponent {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for Exponent {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Exponent{numer: -self.numer, ..self}
+    }
+}
+
+impl Mul for Exponent {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).expect("exponent overflow")
+    }
+}
+
+impl Display for Exponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            Display::fmt(&self.numer, f)
+        } else {
+            Display::fmt(&format!("{}/{}", self.numer, self.denom), f)
+        }
+    }
+}
+
+impl FromStr for Exponent {
+    type Err = ParseError;
+    /// Reads an integer, a fraction like `-1/2` or a decimal number like `-0.5`
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let error = || ParseError::InvalidExponent {
+            text: s.to_owned(),
+            span: 0..s.len(),
+        };
+        let integer = |s: &str| -> Result<i32, ParseError> {
+            let s = s.trim();
+            let digits = s.trim_start_matches('-');
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(error())
+            }
+            s.parse::<i16>().map(i32::from).map_err(|_| error())
+        };
+        let (numer, denom) = if let Some(slash) = s.find('/') {
+            (integer(&s[..slash])?, integer(&s[slash+1..])?)
+        } else if let Some(point) = s.find('.') {
+            let decimals = s.len() - point - 1;
+            if decimals > 4 {
+                return Err(error())
+            }
+            let fraction = if decimals == 0 {0} else {integer(&s[point+1..])?};
+            if fraction < 0 {
+                return Err(error())
+            }
+            let whole = match s[..point].trim() {
+                "" | "-" => 0,
+                whole => integer(whole)?,
+            };
+            let denom = 10i32.pow(decimals as u32);
+            let numer = whole.abs() * denom + fraction;
+            (if s.trim_start().starts_with('-') {-numer} else {numer}, denom)
+        } else {
+            (integer(s)?, 1)
+        };
+        Exponent::checked_reduced(numer as i64, denom as i64).ok_or_else(error)
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Default, Copy, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize), serde(default))]
 /// Struct to keep track of the dimension of a quantity for dimensional analysis.
 /// Based on the SI units.
 pub struct Dimension {
     /// The exponent of length
-    pub length: i16,
+    pub length: Exponent,
     /// The exponent of time
-    pub time: i16,
+    pub time: Exponent,
     /// The exponent of mass
-    pub mass: i16,
+    pub mass: Exponent,
     /// The exponent of current
-    pub current: i16,
+    pub current: Exponent,
     /// The exponent of temperature
-    pub temperature: i16,
+    pub temperature: Exponent,
     /// The exponent of amount of substance
-    pub substance_amount: i16,
+    pub substance_amount: Exponent,
     /// The exponent of luminous intensity
-    pub luminous_intensity: i16
+    pub luminous_intensity: Exponent,
+    /// The exponent of plane angle, which is only used by systems keeping track of angles
+    ///
+    /// Solid angles have an exponent of two, since a steradian is a square radian
+    pub angle: Exponent,
 }
 
 #[test]
 fn super_test() {
     assert_eq!(to_superscript("-124"), "⁻¹²⁴");
     assert_eq!(to_superscript("asdg ja-kage4²"), "asdg ja⁻kage⁴²");
+    assert_eq!(to_superscript("-1/2"), "⁻¹ᐟ²");
+}
+
+#[test]
+fn exponent_test() {
+    assert_eq!(Exponent::new(2, -4), Exponent::new(-1, 2));
+    assert_eq!(Exponent::new(1, 2) + Exponent::new(1, 2), 1);
+    assert_eq!(Exponent::new(1, 3) - Exponent::new(1, 2), Exponent::new(-1, 6));
+    assert!(Exponent::new(1, 2) < Exponent::integer(1));
+    assert_eq!("-0.5".parse::<Exponent>().unwrap(), Exponent::new(-1, 2));
+    assert_eq!("3/6".parse::<Exponent>().unwrap(), Exponent::new(1, 2));
+    assert_eq!("-2".parse::<Exponent>().unwrap(), -2);
+    assert!("1/0".parse::<Exponent>().is_err());
+    assert!("1.-5".parse::<Exponent>().is_err());
+    assert_eq!(Exponent::new(-1, 2).to_string(), "-1/2");
+    assert_eq!(Exponent::approximate(1. / 3.), Some(Exponent::new(1, 3)));
+    assert_eq!(Exponent::approximate(std::f64::consts::PI), None);
+    assert_eq!(FREQUENCY * Exponent::new(1, 2) * 2, FREQUENCY);
+    assert_eq!(Exponent::integer(i16::MAX).checked_add(Exponent::ONE), None);
+    assert_eq!(Exponent::checked_new(1, i16::MIN), None);
+    assert_eq!(Exponent::checked_new(2, -4), Some(Exponent::new(-1, 2)));
+    assert_eq!(Exponent::new(1, 2).checked_mul(Exponent::integer(4)), Some(Exponent::integer(2)));
+    assert_eq!((LENGTH * 30000).checked_add(LENGTH * 30000), None);
+    assert_eq!(LENGTH.checked_sub(TIME), Some(VELOCITY));
+    assert_eq!(format!("{}", VOLTAGE - FREQUENCY * Exponent::new(1, 2)), "[mass]¹[length]²[time]⁻⁵ᐟ²[current]⁻¹");
 }
 
 /// The null-dimension, indicating a dimensionless quantity
-pub const NUL: Dimension = Dimension{mass:0,length:0,time:0,current:0,temperature:0,substance_amount:0,luminous_intensity:0};
+pub const NUL: Dimension = Dimension{
+    mass: Exponent::ZERO,
+    length: Exponent::ZERO,
+    time: Exponent::ZERO,
+    current: Exponent::ZERO,
+    temperature: Exponent::ZERO,
+    substance_amount: Exponent::ZERO,
+    luminous_intensity: Exponent::ZERO,
+    angle: Exponent::ZERO,
+};
 
 macro_rules! dims {
     ($($cnst:ident, $display_name:expr; {$($n:ident : $v:expr),+},)*) => (
         $(
             /// A dimension constant
-            pub const $cnst: Dimension = Dimension{$($n: $v,)+..NUL};
+            pub const $cnst: Dimension = Dimension{$($n: Exponent::integer($v),)+..NUL};
         )*
         impl Display for Dimension {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -46,36 +295,40 @@ macro_rules! dims {
                     }
                 )*}
 
-                let Dimension{mass,length,time,current,temperature,substance_amount,luminous_intensity} = *self;
+                let Dimension{mass,length,time,current,temperature,substance_amount,luminous_intensity,angle} = *self;
                 let mut s = String::new();
-                if mass != 0 {
+                if mass != Exponent::ZERO {
                     s.push_str("[mass]");
                     s.push_str(&to_superscript(&format!("{}",mass)));
                 }
-                if length != 0 {
+                if length != Exponent::ZERO {
                     s.push_str("[length]");
                     s.push_str(&to_superscript(&format!("{}", length)));
                 }
-                if time != 0 {
+                if time != Exponent::ZERO {
                     s.push_str("[time]");
                     s.push_str(&to_superscript(&format!("{}", time)));
                 }
-                if current != 0 {
+                if current != Exponent::ZERO {
                     s.push_str("[current]");
                     s.push_str(&to_superscript(&format!("{}", current)));
                 }
-                if temperature != 0 {
+                if temperature != Exponent::ZERO {
                     s.push_str("[temperature]");
                     s.push_str(&to_superscript(&format!("{}", temperature)));
                 }
-                if substance_amount != 0 {
+                if substance_amount != Exponent::ZERO {
                     s.push_str("[substance amount]");
                     s.push_str(&to_superscript(&format!("{}", substance_amount)));
                 }
-                if luminous_intensity != 0 {
+                if luminous_intensity != Exponent::ZERO {
                     s.push_str("[luminous intensity]");
                     s.push_str(&to_superscript(&format!("{}", luminous_intensity)));
                 }
+                if angle != Exponent::ZERO {
+                    s.push_str("[angle]");
+                    s.push_str(&to_superscript(&format!("{}", angle)));
+                }
                 if s.is_empty() && f.alternate() {
                     s.push_str("Dimensionless")
                 }
@@ -114,15 +367,20 @@ dims!{
     VOLTAGE, "Voltage"; {mass:1,length:2,time:-3,current:-1},
     RESISTANCE, "Resistance"; {mass:1,length:2,time:-3,current:-2},
     CHARGE, "Charge"; {current:1,time:1},
+    CAPACITANCE, "Capacitance"; {mass:-1,length:-2,time:4,current:2},
 
     PRESSURE, "Pressure"; {mass:1,length:-1,time:-2},
+
+    ANGLE, "Angle"; {angle:1},
+    SOLID_ANGLE, "Solid Angle"; {angle:2},
+    ANGULAR_VELOCITY, "Angular Velocity"; {angle:1,time:-1},
 }
 
 impl Add for Dimension {
     type Output = Self;
     fn add(self, rhs: Self) -> Self {
-        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity} = self;
-        let Dimension{length:s, time:t, mass:m, current:i, temperature:temp, substance_amount:n, luminous_intensity:j} = rhs;
+        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity, angle} = self;
+        let Dimension{length:s, time:t, mass:m, current:i, temperature:temp, substance_amount:n, luminous_intensity:j, angle:a} = rhs;
 
         Dimension{
             length: length + s,
@@ -132,6 +390,7 @@ impl Add for Dimension {
             temperature: temperature + temp,
             substance_amount: substance_amount + n,
             luminous_intensity: luminous_intensity + j,
+            angle: angle + a,
         }
     }
 }
@@ -139,8 +398,8 @@ impl Add for Dimension {
 impl Sub for Dimension {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self {
-        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity} = self;
-        let Dimension{length:s, time:t, mass:m, current:i, temperature:temp, substance_amount:n, luminous_intensity:j} = rhs;
+        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity, angle} = self;
+        let Dimension{length:s, time:t, mass:m, current:i, temperature:temp, substance_amount:n, luminous_intensity:j, angle:a} = rhs;
 
         Dimension{
             length: length - s,
@@ -150,14 +409,15 @@ impl Sub for Dimension {
             temperature: temperature - temp,
             substance_amount: substance_amount - n,
             luminous_intensity: luminous_intensity - j,
+            angle: angle - a,
         }
     }
 }
 
-impl Mul<i16> for Dimension {
+impl Mul<Exponent> for Dimension {
     type Output = Self;
-    fn mul(self, factor: i16) -> Self {
-        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity} = self;
+    fn mul(self, factor: Exponent) -> Self {
+        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity, angle} = self;
 
         Dimension{
             length: length * factor,
@@ -167,23 +427,56 @@ impl Mul<i16> for Dimension {
             temperature: temperature * factor,
             substance_amount: substance_amount * factor,
             luminous_intensity: luminous_intensity * factor,
+            angle: angle * factor,
         }
     }
 }
 
+impl Mul<i16> for Dimension {
+    type Output = Self;
+    fn mul(self, factor: i16) -> Self {
+        self * Exponent::integer(factor)
+    }
+}
+
 impl Mul<Dimension> for i16 {
     type Output = Dimension;
     fn mul(self, dim: Dimension) -> Dimension {
-        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity} = dim;
+        dim * Exponent::integer(self)
+    }
+}
 
-        Dimension{
-            length: length * self,
-            time: time * self,
-            mass: mass * self,
-            current: current * self,
-            temperature: temperature * self,
-            substance_amount: substance_amount * self,
-            luminous_intensity: luminous_intensity * self,
-        }
+impl Dimension {
+    /// Whether every exponent is a whole number
+    pub fn is_integral(&self) -> bool {
+        let Dimension{length, time, mass, current, temperature, substance_amount, luminous_intensity, angle} = *self;
+        [length, time, mass, current, temperature, substance_amount, luminous_intensity, angle]
+            .iter()
+            .all(|e| e.is_integer())
+    }
+    /// Combines each exponent with the one of `rhs` by `f`, returning `None` if `f` does
+    fn try_zip<F: Fn(Exponent, Exponent) -> Option<Exponent>>(self, rhs: Self, f: F) -> Option<Self> {
+        Some(Dimension{
+            length: f(self.length, rhs.length)?,
+            time: f(self.time, rhs.time)?,
+            mass: f(self.mass, rhs.mass)?,
+            current: f(self.current, rhs.current)?,
+            temperature: f(self.temperature, rhs.temperature)?,
+            substance_amount: f(self.substance_amount, rhs.substance_amount)?,
+            luminous_intensity: f(self.luminous_intensity, rhs.luminous_intensity)?,
+            angle: f(self.angle, rhs.angle)?,
+        })
+    }
+    /// Adds two dimensions, returning `None` if an exponent overflows
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.try_zip(rhs, Exponent::checked_add)
+    }
+    /// Subtracts two dimensions, returning `None` if an exponent overflows
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.try_zip(rhs, Exponent::checked_sub)
+    }
+    /// Multiplies the exponents by `factor`, returning `None` if one overflows
+    pub fn checked_mul(self, factor: Exponent) -> Option<Self> {
+        self.try_zip(NUL, |e, _| e.checked_mul(factor))
     }
 }
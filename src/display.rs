@@ -1,4 +1,6 @@
 use std::fmt::{self, Display};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
 use num::Float;
 
 use super::*;
@@ -9,59 +11,675 @@ use super::*;
 pub struct UnitDisplay<'a, N: 'a + Float> {
     val: &'a Value<N>,
     sys: &'a UnitSystem<N>,
+    prefixes: Option<PrefixOptions>,
+    preferred: Option<Vec<(String, Unit<N>)>>,
+    compound: Option<Vec<(String, Unit<N>)>>,
+    uncertainty: Option<N>,
+    notation: UncertaintyNotation,
+    long_names: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How to write the uncertainty of a value
+pub enum UncertaintyNotation {
+    /// Like `9.81 ± 0.02 m/s²`
+    PlusMinus,
+    /// Like `9.81(2) m/s²`, with the uncertainty in the last digits in parentheses
+    Concise,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Options for choosing an SI prefix when displaying a value
+pub struct PrefixOptions {
+    /// Only use the prefixes whose exponent is a multiple of three, so no centi, deci, deca or hecto
+    pub engineering: bool,
+    /// Units that never get a prefix besides the ones that aren't prefixable in the system
+    pub forbidden: HashSet<String>,
+    /// Prefixes always used for a dimension instead of choosing one
+    pub pinned: HashMap<Dimension, Prefix>,
 }
 
 pub fn make_display<'a, N: 'a + Float>(sys: &'a UnitSystem<N>, val: &'a Value<N>) -> UnitDisplay<'a, N> {
-    UnitDisplay {val, sys}
+    UnitDisplay {
+        val,
+        sys,
+        prefixes: None,
+        preferred: None,
+        compound: None,
+        uncertainty: None,
+        notation: UncertaintyNotation::PlusMinus,
+        long_names: false,
+    }
 }
 
-impl<'a, N: 'a + Float + Display> Display for UnitDisplay<'a, N> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl<'a, N: 'a + Float> UnitDisplay<'a, N> {
+    /// Chooses an SI prefix for the unit, so that the number is at least 1 and less than 1000
+    ///
+    /// The prefix goes on the first unit if it is prefixable and not raised to a power.
+    /// A prefixed unit like kg has its prefix replaced, so 0.0012 kg is written as 1.2 g
+    pub fn auto_prefix(self) -> Self {
+        self.with_prefixes(PrefixOptions::default())
+    }
+    /// Chooses an SI prefix for the unit like `auto_prefix` using the given options
+    pub fn with_prefixes(mut self, options: PrefixOptions) -> Self {
+        self.prefixes = Some(options);
+        self
+    }
+    /// Displays the value in these units instead of the preferred units of the system
+    ///
+    /// Units not of the dimension of the value are ignored
+    pub fn prefer(mut self, units: &[&str]) -> Result<Self, ParseError> {
+        let units = units.iter()
+            .map(|&unit| Ok((unit.to_owned(), self.sys.unit_from_str(unit)?)))
+            .collect::<Result<_, ParseError>>()?;
+        self.preferred = Some(units);
+        Ok(self)
+    }
+    /// Displays the value as a sum of the given units, like `1 h 23 min 4 s` or `5 ft 3 in`
+    ///
+    /// Parts are written from the largest unit to the smallest, leaving out those that are zero.
+    /// All but the last part are whole numbers. The precision of the formatter rounds the
+    /// last part, carrying over into the parts before it.
+    /// Fails if a unit can't be read or doesn't have the dimension of the value, and for
+    /// affine and logarithmic units, which can't be added up
+    pub fn compound(mut self, units: &[&str]) -> Result<Self, UnitError> {
+        let mut units = units.iter()
+            .map(|&unit| {
+                let u = self.sys.unit_from_str(unit)?;
+                if u.dimension != self.val.1.dimension {
+                    return Err(UnitError::DimensionMismatch(self.val.1.dimension, u.dimension))
+                }
+                if u.is_affine() {
+                    return Err(UnitError::AbsoluteAddition)
+                }
+                if u.is_logarithmic() {
+                    return Err(UnitError::LogarithmicAddition)
+                }
+                Ok((unit.to_owned(), u))
+            })
+            .collect::<Result<Vec<_>, UnitError>>()?;
+        units.sort_by(|a, b| total_cmp(b.1.factor, a.1.factor));
+        self.compound = Some(units);
+        Ok(self)
+    }
+    /// Writes the value with a standard uncertainty in the unit of the value
+    ///
+    /// Without a precision, the number and the uncertainty get as many decimals as the first
+    /// significant digit of the uncertainty needs. So an uncertainty of at least one is rounded
+    /// to a whole number, like in `1234 ± 56`, and a smaller one to one significant digit,
+    /// like in `9.81 ± 0.02`
+    pub fn with_uncertainty(mut self, uncertainty: N) -> Self {
+        self.uncertainty = Some(uncertainty);
+        self
+    }
+    /// Sets how the uncertainty is written
+    pub fn notation(mut self, notation: UncertaintyNotation) -> Self {
+        self.notation = notation;
+        self
+    }
+    /// Writes units with their long names, like `3 metres per second` instead of `3 m·s⁻¹`
+    ///
+    /// The last unit before `per` is plural unless the number is one.
+    /// Units without long names are written with their symbols
+    pub fn long_names(mut self) -> Self {
+        self.long_names = true;
+        self
+    }
+    /// The long name of the unit `symbol`, or the symbol if it has none
+    fn long_name<'b>(&'b self, symbol: &'b str, plural: bool) -> &'b str {
+        self.sys.long_name(symbol, plural).unwrap_or(symbol)
+    }
+    /// Writes the terms with long names, like `kilograms per cubic metre`
+    fn write_long_terms(&self, terms: &[(&str, Exponent)], prefix: Option<Prefix>, plural: bool) -> String {
+        let (numerator, denominator): (Vec<_>, Vec<_>) = terms.iter().partition(|&&(_, exp)| exp > Exponent::ZERO);
+        let mut words = Vec::new();
+        for (i, &&(symbol, exp)) in numerator.iter().enumerate() {
+            let name = self.long_name(symbol, plural && i+1 == numerator.len());
+            let name = format!("{}{}", if i == 0 {prefix.map_or("", |p| p.name)} else {""}, name);
+            words.push(power_words(&name, exp));
+        }
+        if !denominator.is_empty() {
+            words.push("per".to_owned());
+        }
+        for &&(symbol, exp) in &denominator {
+            let (name, exp) = (self.long_name(symbol, false), -exp);
+            // Powers of time read like `per second squared`, and others like `per cubic metre`
+            let time = self.sys.get_unit(symbol).is_some_and(|u| u.dimension == TIME);
+            words.push(if exp == 2 && time {
+                format!("{} squared", name)
+            } else if exp == 3 && time {
+                format!("{} cubed", name)
+            } else {
+                power_words(name, exp)
+            });
+        }
+        words.join(" ")
+    }
+    /// Returns the unit and the terms to write it with
+    ///
+    /// Of the preferred units of the same dimension, the first giving a number of
+    /// at least one is used, or else the last of them. Affine units are only used for
+    /// absolute values and other units only for differences, and logarithmic units
+    /// only for levels on the same scale.
+    /// Without any preferred units, the unit with the closest factor is used,
+    /// or a combination of named units if that has a factor too different
+    fn choose_unit(&self) -> (Unit<N>, Vec<(&str, Exponent)>) {
+        let preferred = match self.preferred {
+            Some(ref preferred) => &preferred[..],
+            None => self.sys.preferred.get(&self.val.1.dimension).map_or(&[][..], |p| &p[..]),
+        };
+        let preferred: Vec<_> = preferred.iter()
+            .filter(|&&(_, u)| u.dimension==self.val.1.dimension && u.is_affine()==self.val.1.is_affine() && u.log==self.val.1.log)
+            .map(|(name, u)| (self.val.1.convert(self.val.0, u), name, *u))
+            .collect();
+        if let Some(&(_, name, u)) = preferred.iter().find(|&&(n, _, _)| n.abs() >= N::one()).or(preferred.last()) {
+            return (u, vec![(name, Exponent::ONE)])
+        }
+
+        // Plain numbers aren't written in dimensionless units like rad
+        if self.val.1 == Unit::new(NUL) {
+            return (self.val.1, Vec::new())
+        }
         let nu = self.sys.units
             .iter()
-            .filter(|&(_, u)| u.dimension==self.val.1.dimension)
-            .min_by(|&(_, u), &(_, u2)| {
-                (u.factor-self.val.1.factor).abs().partial_cmp(&(u2.factor-self.val.1.factor).abs()).unwrap()
+            .filter(|&(_, u)| u.dimension==self.val.1.dimension && u.is_affine()==self.val.1.is_affine() && u.log==self.val.1.log)
+            .min_by(|&(n, u), &(n2, u2)| {
+                total_cmp((u.factor-self.val.1.factor).abs(), (u2.factor-self.val.1.factor).abs())
+                    .then_with(|| n.cmp(n2))
             });
 
         if let Some((name, unit)) = nu {
-            if self.val.1.factor != N::one() || unit.factor == N::one() {
-                (self.val.0  * self.val.1.factor / unit.factor).fmt(f)?;
-                return write!(f, " {}", name)
+            if self.val.1.factor != N::one() || unit.factor == N::one() || unit.is_affine() {
+                return (*unit, vec![(name, Exponent::ONE)])
             }
         }
 
-        let Dimension{mass,length,time,current,temperature,substance_amount,luminous_intensity} = self.val.1.dimension;
-        let mut s = String::new();
-        if mass != 0 {
-            s.push_str(self.sys.base.mass);
-            s.push_str(&to_superscript(&format!("{}",mass)));
+        (Unit::new(self.val.1.dimension), simplify(self.sys, self.val.1.dimension))
+    }
+    /// Returns the prefix of the first of the units and the prefixable unit it is on,
+    /// if the first unit is a prefixed unit like kg that isn't prefixable itself
+    fn own_prefix<'b>(&self, options: &PrefixOptions, n: N, terms: &[(&'b str, Exponent)]) -> Option<(Prefix, &'b str)> {
+        let name = match terms.first() {
+            Some(&(name, Exponent::ONE)) if !self.sys.prefixable.contains(name) && !options.forbidden.contains(name) => name,
+            _ => return None,
+        };
+        // Without a prefix to choose, the unit stays as it is
+        if n == N::zero() || !n.is_finite() {
+            return None
+        }
+        let unit = self.sys.units.get(name)?;
+        // Only a split giving back the same unit, so mi isn't taken for milli-i
+        split_prefix(name).find(|&(prefix, rest)| {
+            self.sys.prefixable.contains(rest) && !options.forbidden.contains(rest)
+                && self.sys.units.get(rest).is_some_and(|u| {
+                    let factor = u.factor * prefix.factor();
+                    u.dimension == unit.dimension && u.offset == unit.offset && u.log == unit.log
+                        && (factor - unit.factor).abs() <= unit.factor.abs() * N::from(1e-9).unwrap()
+                })
+        })
+    }
+    /// Returns the prefix to put on the first of the units
+    fn choose_prefix(&self, options: &PrefixOptions, n: N, terms: &[(&str, Exponent)]) -> Option<Prefix> {
+        match terms.first() {
+            Some(&(name, Exponent::ONE)) if self.sys.prefixable.contains(name) && !options.forbidden.contains(name) => (),
+            _ => return None,
+        }
+        if let Some(&prefix) = options.pinned.get(&self.val.1.dimension) {
+            return Some(prefix)
+        }
+        if n == N::zero() || !n.is_finite() || n.abs() >= N::one() && n.abs() < N::from(1000).unwrap() {
+            return None
+        }
+
+        let unprefixed = Prefix {symbol: "", name: "", exponent: 0};
+        let mut prefixes: Vec<_> = SI_PREFIXES.iter()
+            .cloned()
+            .chain(Some(unprefixed))
+            .filter(|p| !options.engineering || p.exponent % 3 == 0)
+            .collect();
+        prefixes.sort_by_key(|p| p.exponent);
+        // The largest prefix leaving a number of at least one, or the smallest if there is none
+        let prefix = *prefixes.iter().rev().find(|p| n.abs() / p.factor() >= N::one()).unwrap_or(&prefixes[0]);
+        if prefix == unprefixed {None} else {Some(prefix)}
+    }
+}
+
+impl<'a, N: 'a + Float + Display> UnitDisplay<'a, N> {
+    fn fmt_uncertain(&self, n: N, uncertainty: N, f: &mut fmt::Formatter) -> fmt::Result {
+        let decimals = f.precision().unwrap_or_else(|| {
+            let magnitude = uncertainty.log10().floor();
+            if magnitude < N::zero() {(-magnitude).to_usize().unwrap_or(0)} else {0}
+        });
+        match self.notation {
+            UncertaintyNotation::PlusMinus => write!(f, "{:.*} ± {:.*}", decimals, n, decimals, uncertainty),
+            UncertaintyNotation::Concise => {
+                let digits = (uncertainty * N::from(10).unwrap().powi(decimals as i32)).round();
+                write!(f, "{:.*}({})", decimals, n, digits)
+            }
+        }
+    }
+    fn fmt_compound(&self, units: &[(String, Unit<N>)], f: &mut fmt::Formatter) -> fmt::Result {
+        let (last_name, last) = units.last().unwrap();
+        let mut rest = self.val.1.convert(self.val.0, last);
+        if rest < N::zero() {
+            write!(f, "-")?;
+            rest = -rest;
+        }
+        if let Some(precision) = f.precision() {
+            let scale = N::from(10).unwrap().powi(precision as i32);
+            rest = (rest * scale).round() / scale;
+        }
+        let total = rest;
+
+        let mut first = true;
+        for (name, unit) in &units[..units.len()-1] {
+            let mut ratio = unit.factor / last.factor;
+            // Avoid 60 in becoming 4 ft 12 in, because a foot is 12.000000000000002 in
+            if (ratio - ratio.round()).abs() <= ratio * N::from(1e-9).unwrap() {
+                ratio = ratio.round();
+            }
+            let count = (rest / ratio).floor();
+            if count > N::zero() {
+                if !first {
+                    write!(f, " ")?;
+                }
+                let name = if self.long_names {self.long_name(name, count != N::one())} else {name};
+                write!(f, "{} {}", count, name)?;
+                rest = round_noise((rest - count * ratio).max(N::zero()), total);
+                first = false;
+            }
+        }
+        if first || rest != N::zero() {
+            if !first {
+                write!(f, " ")?;
+            }
+            Display::fmt(&rest, f)?;
+            let last_name = if self.long_names {self.long_name(last_name, rest != N::one())} else {last_name};
+            write!(f, " {}", last_name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, N: 'a + Float + Display> Display for UnitDisplay<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref units) = self.compound {
+            if !units.is_empty() {
+                return self.fmt_compound(units, f)
+            }
+        }
+        let (unit, mut terms) = self.choose_unit();
+        let mut n = self.val.1.convert(self.val.0, &unit);
+        let mut uncertainty = self.uncertainty
+            .filter(|&u| u != N::zero())
+            .map(|u| u * self.val.1.base_slope(self.val.0) / unit.base_slope(n));
+        if let Some(ref options) = self.prefixes {
+            // A unit with a prefix of its own like kg gets its prefix chosen like g
+            if let Some((own, name)) = self.own_prefix(options, n, &terms) {
+                n = n * own.factor();
+                uncertainty = uncertainty.map(|u| u * own.factor());
+                terms[0].0 = name;
+            }
+        }
+        let prefix = self.prefixes.as_ref().and_then(|options| self.choose_prefix(options, n, &terms));
+        if let Some(prefix) = prefix {
+            n = n / prefix.factor();
+            uncertainty = uncertainty.map(|u| u / prefix.factor());
+        }
+
+        match uncertainty {
+            Some(u) => self.fmt_uncertain(n, u.abs(), f)?,
+            None => Display::fmt(&n, f)?,
+        }
+        if !terms.is_empty() {
+            if self.long_names {
+                write!(f, " {}", self.write_long_terms(&terms, prefix, n != N::one() || uncertainty.is_some()))?;
+            } else {
+                write!(f, " {}{}", prefix.map_or("", |p| p.symbol), write_terms(&terms))?;
+            }
         }
-        if length != 0 {
-            s.push_str(self.sys.base.length);
-            s.push_str(&to_superscript(&format!("{}", length)));
+        Ok(())
+    }
+}
+
+/// Orders numbers like `f64::total_cmp`, with NaN after all other numbers
+fn total_cmp<N: Float>(a: N, b: N) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.partial_cmp(&b).unwrap(),
+        (a_nan, b_nan) => a_nan.cmp(&b_nan),
+    }
+}
+
+/// Rounds away the rounding errors left after subtracting from `total`,
+/// so 0.6699999999999591 becomes 0.67
+fn round_noise<N: Float>(n: N, total: N) -> N {
+    if total == N::zero() || !total.is_finite() {
+        return n
+    }
+    let ten = N::from(10).unwrap();
+    let place = (total.abs() * N::epsilon() * N::from(1000).unwrap()).log10().ceil();
+    let scale = ten.powf(-place);
+    (n * scale).round() / scale
+}
+
+/// Writes a long name raised to a power, like `square metres`
+fn power_words(name: &str, exp: Exponent) -> String {
+    if exp == 2 {
+        format!("square {}", name)
+    } else if exp == 3 {
+        format!("cubic {}", name)
+    } else if exp == 1 {
+        name.to_owned()
+    } else {
+        format!("{} to the power {}", name, exp)
+    }
+}
+
+/// Writes the terms as symbols with superscript exponents separated by `·`
+pub fn write_terms(terms: &[(&str, Exponent)]) -> String {
+    let mut s = String::new();
+    for &(name, exp) in terms {
+        if !s.is_empty() {
+            s.push('·');
         }
-        if time != 0 {
-            s.push_str(self.sys.base.time);
-            s.push_str(&to_superscript(&format!("{}", time)));
+        s.push_str(name);
+        if exp != Exponent::ONE {
+            s.push_str(&to_superscript(&format!("{}", exp)));
         }
-        if current != 0 {
-            s.push_str(self.sys.base.current);
-            s.push_str(&to_superscript(&format!("{}", current)));
+    }
+    s
+}
+
+/// The exponents of a dimension in the order its base units are written in
+fn exponents(dim: Dimension) -> [Exponent; 8] {
+    let Dimension{mass,length,time,current,temperature,substance_amount,luminous_intensity,angle} = dim;
+    [mass, length, time, current, temperature, substance_amount, luminous_intensity, angle]
+}
+
+/// Writes a dimension in terms of the base units of the system
+fn base_terms<N: Float>(sys: &UnitSystem<N>, dim: Dimension) -> Vec<(&str, Exponent)> {
+    let base = &sys.base;
+    let names = [
+        &*base.mass,
+        &*base.length,
+        &*base.time,
+        &*base.current,
+        &*base.temperature,
+        &*base.substance_amount,
+        &*base.luminous_intensity,
+        // Only systems keeping track of angles have a base unit for them
+        base.angle.as_deref().unwrap_or("rad"),
+    ];
+    names.iter().cloned().zip(exponents(dim).iter().cloned()).filter(|&(_, exp)| exp != 0).collect()
+}
+
+/// Finds the simplest way to write a dimension with the named units of the system
+///
+/// Writes it in base units, unless up to two coherent derived units (like N or W) times
+/// base units need fewer symbols, where derived units may also be raised to half powers
+/// like in V·Hz⁻¹ᐟ². So m/s² stays m·s⁻² rather than N·kg⁻¹. A dimension with base units
+/// in the numerator keeps a unit there, so A/V isn't written as Ω⁻¹.
+/// Of the shortest ways, keeps the one with the fewest base units with fractional exponents,
+/// then the fewest fractional exponents, then the fewest derived units, then the fewest
+/// negative exponents, then the smallest exponents, then the one whose derived units stand
+/// for the most base units, like W·m⁻¹·K⁻¹ rather than N·s⁻¹·K⁻¹.
+/// Remaining ties are broken by the written form, so a dimension is always written the same way.
+pub fn simplify<'a, N: Float>(sys: &'a UnitSystem<N>, dim: Dimension) -> Vec<(&'a str, Exponent)> {
+    let base_dimensions = [MASS, LENGTH, TIME, CURRENT, TEMPERATURE, AMOUNT_OF_SUBSTANCE, LUMINOUS_INTENSITY, ANGLE];
+    // Units of the same dimension are written the same, so only the first by name is tried
+    let mut by_dimension: HashMap<Dimension, &'a str> = HashMap::new();
+    for (name, u) in &sys.units {
+        if u.factor == N::one() && !u.is_affine() && !u.is_logarithmic() && u.dimension != NUL
+            && !base_dimensions.contains(&u.dimension)
+        {
+            let first = by_dimension.entry(u.dimension).or_insert(name);
+            if **name < **first {
+                *first = name;
+            }
         }
-        if temperature != 0 {
-            s.push_str(self.sys.base.temperature);
-            s.push_str(&to_superscript(&format!("{}", temperature)));
+    }
+    let mut derived: Vec<_> = by_dimension.into_iter().map(|(d, name)| (name, d)).collect();
+    derived.sort_by_key(|&(name, _)| name);
+
+    let half = Exponent::new(1, 2);
+    let whole = [1, -1, 2, -2, 3, -3].iter().map(|&e| Exponent::integer(e));
+    let singles: Vec<_> = whole.chain(vec![half, -half]).collect();
+    let (one, minus_one) = (Exponent::ONE, -Exponent::ONE);
+    let pairs = [
+        (one, one), (one, minus_one), (minus_one, one), (minus_one, minus_one),
+        (one, half), (one, -half), (minus_one, half), (minus_one, -half),
+        (half, one), (-half, one), (half, minus_one), (-half, minus_one),
+    ];
+    let has_numerator = exponents(dim).iter().any(|&exp| exp > Exponent::ZERO);
+    // The base units a dimension has, one bit each
+    let support = |d: Dimension| exponents(d).iter().enumerate()
+        .filter(|&(_, &exp)| exp != 0)
+        .fold(0u8, |bits, (i, _)| bits | 1 << i);
+
+    // Every derived unit raised to every exponent, with the dimension that has,
+    // how many base units it stands for and what is left to write in base units
+    let raised: Vec<Vec<_>> = derived.iter()
+        .map(|&(name, d)| singles.iter().map(|&exp| {
+            let part = d.checked_mul(exp)?;
+            let covered = exponents(part).iter().try_fold(Exponent::ZERO, |sum, e| sum.checked_add(e.abs()))?;
+            Some((name, exp, part, covered, dim.checked_sub(part)?))
+        }).collect())
+        .collect();
+    let pairs: Vec<_> = pairs.iter()
+        .map(|&(exp, exp2)| {
+            let index = |exp| singles.iter().position(|&e| e == exp).unwrap();
+            (index(exp), index(exp2))
+        })
+        .collect();
+
+    // Scores a candidate without writing it, so that only the best ones are written
+    let score = |named: &[(&'a str, Exponent, Dimension)], rest: Dimension, covered: Exponent| {
+        let base = exponents(rest);
+        let exps = || named.iter().map(|&(_, exp, _)| exp).chain(base.iter().cloned().filter(|&exp| exp != 0));
+        if has_numerator && exps().all(|exp| exp < Exponent::ZERO) {
+            return None
         }
-        if substance_amount != 0 {
-            s.push_str(self.sys.base.substance_amount);
-            s.push_str(&to_superscript(&format!("{}", substance_amount)));
+        let fractional_base = base.iter().filter(|exp| !exp.is_integer()).count();
+        let terms = exps().count();
+        let fractional = exps().filter(|exp| !exp.is_integer()).count();
+        let negative = exps().filter(|&exp| exp < Exponent::ZERO).count();
+        let sum = exps().try_fold(Exponent::ZERO, |sum, exp| sum.checked_add(exp.abs()))?;
+        Some((terms, fractional_base, fractional, named.len(), negative, sum, Reverse(covered)))
+    };
+    let write = |named: &[(&'a str, Exponent, Dimension)], rest: Dimension| {
+        let mut terms: Vec<_> = named.iter().map(|&(name, exp, _)| (name, exp)).collect();
+        terms.extend(base_terms(sys, rest));
+        // Stable sort, so derived units come before base units with the same sign
+        terms.sort_by_key(|&(_, exp)| exp < Exponent::ZERO);
+        terms
+    };
+
+    type Score = (usize, usize, usize, usize, usize, Exponent, Reverse<Exponent>);
+    let mut best = (score(&[], dim, Exponent::ZERO).unwrap(), write(&[], dim));
+    let consider = |best: &mut (Score, Vec<(&'a str, Exponent)>), named: &[(&'a str, Exponent, Dimension)], rest: Dimension, covered: Exponent| {
+        if let Some(key) = score(named, rest, covered) {
+            if key < best.0 || key == best.0 && write_terms(&write(named, rest)) < write_terms(&best.1) {
+                *best = (key, write(named, rest));
+            }
         }
-        if luminous_intensity != 0 {
-            s.push_str(self.sys.base.luminous_intensity);
-            s.push_str(&to_superscript(&format!("{}", luminous_intensity)));
+    };
+    for &(name, exp, part, covered, rest) in raised.iter().flatten().flatten() {
+        consider(&mut best, &[(name, exp, part)], rest, covered);
+    }
+    for (i, parts) in raised.iter().enumerate() {
+        for parts2 in &raised[i+1..] {
+            for &(k, k2) in &pairs {
+                let ((name, exp, part, covered, rest), (name2, exp2, part2, covered2, _)) = match (parts[k], parts2[k2]) {
+                    (Some(first), Some(second)) => (first, second),
+                    _ => continue,
+                };
+                // Base units left by the first unit that the second doesn't have stay in the candidate,
+                // so skip it if even without more it can't beat the best one so far
+                let (terms, fractional_base, fractional, derived_count, ..) = best.0;
+                let least = 2 + (support(rest) & !support(part2)).count_ones() as usize;
+                if (least, 0, 0, 2) > (terms, fractional_base, fractional, derived_count) {
+                    continue
+                }
+                if let (Some(rest), Some(covered)) = (rest.checked_sub(part2), covered.checked_add(covered2)) {
+                    consider(&mut best, &[(name, exp, part), (name2, exp2, part2)], rest, covered);
+                }
+            }
         }
-        Display::fmt(&(self.val.0 * self.val.1.factor), f)?;
-        write!(f, " {}", s)
     }
+    best.1
+}
+
+#[test]
+fn prefix_display_test() {
+    let si = UnitSystem::<f64>::si();
+    let show = |v: f64, unit, options: PrefixOptions| format!("{:.1}", si.display(&si.val(v, unit).unwrap()).with_prefixes(options));
+
+    assert_eq!(show(0.0012, "A", PrefixOptions::default()), "1.2 mA");
+    assert_eq!(show(3400000., "Pa", PrefixOptions::default()), "3.4 MPa");
+    assert_eq!(show(340., "Pa", PrefixOptions::default()), "340.0 Pa");
+    assert_eq!(show(0.05, "m", PrefixOptions::default()), "5.0 cm");
+    assert_eq!(show(0.05, "m", PrefixOptions{engineering: true, ..Default::default()}), "50.0 mm");
+    assert_eq!(show(0.0012, "kg", PrefixOptions::default()), "1.2 g");
+    assert_eq!(show(5., "kg", PrefixOptions::default()), "5.0 kg");
+    assert_eq!(show(3000., "kg", PrefixOptions::default()), "3.0 Mg");
+    assert_eq!(show(0., "kg", PrefixOptions::default()), "0.0 kg");
+    assert_eq!(show(1200., "°C", PrefixOptions::default()), "1200.0 °C");
+    assert_eq!(show(12., "mW·s⁻¹", PrefixOptions{engineering: true, ..Default::default()}), "12.0 mW·s⁻¹");
+
+    let mut forbidden = PrefixOptions::default();
+    forbidden.forbidden.insert("A".to_owned());
+    assert_eq!(show(0.0012, "A", forbidden), "0.0 A");
+    let mut pinned = PrefixOptions::default();
+    pinned.pinned.insert(PRESSURE, prefix_from_symbol("k").unwrap());
+    assert_eq!(show(3400000., "Pa", pinned), "3400.0 kPa");
+
+    // A unit defined with a NaN factor is never the closest one
+    let mut odd = UnitSystem::<f64>::si();
+    odd.add_unit("nan", Unit::with_factor(LENGTH, f64::NAN));
+    assert_eq!(odd.display(&odd.val(2., "m").unwrap()).to_string(), "2 m");
+}
+
+#[test]
+fn preferred_display_test() {
+    let mut si = UnitSystem::<f64>::si();
+    si.prefer("km/h").unwrap();
+    si.prefer("bar").unwrap();
+    si.prefer("kWh").unwrap();
+    si.prefer("h").unwrap();
+    si.prefer("min").unwrap();
+    si.prefer("s").unwrap();
+    let show = |v: f64, unit| format!("{:.1}", si.display(&si.val(v, unit).unwrap()));
+
+    assert_eq!(show(10., "m/s"), "36.0 km/h");
+    assert_eq!(show(250000., "Pa"), "2.5 bar");
+    assert_eq!(show(7.2, "MJ"), "2.0 kWh");
+    assert_eq!(show(5400., "s"), "1.5 h");
+    assert_eq!(show(90., "s"), "1.5 min");
+    assert_eq!(show(0.5, "s"), "0.5 s");
+    assert_eq!(show(2., "m"), "2.0 m");
+
+    let v = si.val(10., "m/s").unwrap();
+    assert_eq!(si.display(&v).prefer(&["mm/s", "s"]).unwrap().to_string(), "10000 mm/s");
+    assert!(si.display(&v).prefer(&["kmh"]).is_err());
+}
+
+#[test]
+fn compound_display_test() {
+    let us = UnitSystem::<f64>::us_customary();
+    let show = |v: &str, units: &[&str]| format!("{:.0}", us.display(&us.val_s(v).unwrap()).compound(units).unwrap());
+
+    assert_eq!(show("4984 s", &["h", "min", "s"]), "1 h 23 min 4 s");
+    assert_eq!(show("3604 s", &["s", "min", "h"]), "1 h 4 s");
+    assert_eq!(show("1.6 m", &["ft", "in"]), "5 ft 3 in");
+    assert_eq!(show("60 in", &["ft", "in"]), "5 ft");
+    assert_eq!(show("3599.7 s", &["h", "min", "s"]), "1 h");
+    assert_eq!(show("-90 s", &["min", "s"]), "-1 min 30 s");
+    assert_eq!(show("0.2 s", &["min", "s"]), "0 s");
+    assert_eq!(show("36 oz", &["lb", "oz"]), "2 lb 4 oz");
+    assert_eq!(format!("{:.1}", us.display(&us.val_s("1.6 m").unwrap()).compound(&["ft", "in"]).unwrap()), "5 ft 3.0 in");
+    assert!(us.display(&us.val_s("1 s").unwrap()).compound(&["ft"]).is_err());
+    assert_eq!(us.display(&us.val_s("527.67 °F").unwrap()).compound(&["°F", "Δ°F"]).err(), Some(UnitError::AbsoluteAddition));
+    assert_eq!(format!("{}", us.display(&us.val_s("2.47 Δ°F").unwrap()).compound(&["K", "Δ°F"]).unwrap()), "1 K 0.67 Δ°F");
+    assert_eq!(format!("{}", us.display(&us.val_s("1.1 ft").unwrap()).compound(&["ft", "in"]).unwrap()), "1 ft 1.2 in");
+}
+
+#[test]
+fn rational_display_test() {
+    let si = UnitSystem::<f64>::si();
+    let noise = si.val_s("4 nV/Hz^(1/2)").unwrap();
+    assert_eq!(write_terms(&[("V", Exponent::ONE), ("Hz", Exponent::new(-1, 2))]), "V·Hz⁻¹ᐟ²");
+    assert_eq!(format!("{:.0}", si.display(&noise).auto_prefix()), "4 nV·Hz⁻¹ᐟ²");
+}
+
+#[test]
+fn uncertain_display_test() {
+    let si = UnitSystem::<f64>::si();
+    let v = si.uncertain_s("9.81 ± 0.02 m/s").unwrap();
+    assert_eq!(si.display_uncertain(&v).to_string(), "9.81 ± 0.02 m·s⁻¹");
+    assert_eq!(si.display_uncertain(&v).notation(UncertaintyNotation::Concise).to_string(), "9.81(2) m·s⁻¹");
+    assert_eq!(format!("{:.3}", si.display_uncertain(&v)), "9.810 ± 0.020 m·s⁻¹");
+
+    let current = si.uncertain_s("0.00123(4) A").unwrap();
+    assert_eq!(si.display_uncertain(&current).auto_prefix().to_string(), "1.23 ± 0.04 mA");
+    let length = si.uncertain_s("1234 ± 56 m").unwrap();
+    assert_eq!(si.display_uncertain(&length).notation(UncertaintyNotation::Concise).to_string(), "1234(56) m");
+    assert_eq!(si.display_uncertain(&Uncertain::exact(si.val(2., "m").unwrap())).to_string(), "2 m");
+}
+
+#[test]
+fn simplify_test() {
+    let si = UnitSystem::<f64>::si();
+    let write = |dim| write_terms(&simplify(&si, dim));
+
+    assert_eq!(write(POWER), "W");
+    assert_eq!(write(POWER - TIME), "W·s⁻¹");
+    assert_eq!(write(MOMENTUM), "N·s");
+    assert_eq!(write(ACTION), "J·s");
+    assert_eq!(write(VELOCITY), "m·s⁻¹");
+    assert_eq!(write(VOLTAGE - LENGTH), "V·m⁻¹");
+    assert_eq!(write(PRESSURE + TIME), "Pa·s");
+    assert_eq!(write(ENERGY - TEMPERATURE), "J·K⁻¹");
+    assert_eq!(write(NUL), "");
+    assert_eq!(write(ACCELERATION), "m·s⁻²");
+    assert_eq!(write(VOLUME - TIME), "m³·s⁻¹");
+    assert_eq!(write(POWER - LENGTH - TEMPERATURE), "W·m⁻¹·K⁻¹");
+    assert_eq!(write(VOLTAGE + TIME), "V·s");
+    assert_eq!(write(CURRENT - VOLTAGE), "F·s⁻¹");
+    assert_eq!(write(NUL - VOLUME), "m⁻³");
+
+    let v = Value(2., Unit::new(POWER - TIME));
+    assert_eq!(si.display(&v).to_string(), "2 W·s⁻¹");
+}
+
+#[test]
+fn long_names_test() {
+    let si = UnitSystem::<f64>::si();
+    let long = |v: &str| si.display(&si.val_s(v).unwrap()).long_names().to_string();
+
+    assert_eq!(long("3 m/s"), "3 metres per second");
+    assert_eq!(long("1 m"), "1 metre");
+    assert_eq!(long("0.5 K/s²"), "0.5 kelvins per second squared");
+    assert_eq!(long("2 m²"), "2 square metres");
+    assert_eq!(long("5 kg/m^3"), "5 kilograms per cubic metre");
+    assert_eq!(long("20 °C"), "20 degrees Celsius");
+    assert_eq!(long("1 J/K"), "1 joule per kelvin");
+    assert_eq!(format!("{}", si.display(&si.val_s("1500 m").unwrap()).auto_prefix().long_names()), "1.5 kilometres");
+    assert_eq!(format!("{:.0}", si.display(&si.val_s("3601 s").unwrap()).compound(&["h", "s"]).unwrap().long_names()), "1 hour 1 second");
+
+    for s in &["3 metres per second", "0.5 kelvins per second squared", "2 square metres", "5 kilograms per cubic metre"] {
+        assert_eq!(long(s), *s);
+    }
+
+    // Names of several words are read back as well
+    let us = UnitSystem::<f64>::us_customary();
+    for &(sys, s) in &[(&si, "20 °C"), (&si, "3 kWh"), (&si, "1 Wh"), (&us, "12 floz"), (&us, "30 psi"), (&us, "2 lbf")] {
+        let Value(n, unit) = sys.val_s(s).unwrap();
+        let long = sys.display(&Value(n, unit)).long_names().to_string();
+        let Value(read, read_unit) = sys.val_s(&long).unwrap();
+        assert_eq!(read_unit.dimension, unit.dimension, "{} is {}", s, long);
+        assert!((read_unit.to_base(read) - unit.to_base(n)).abs() < 1e-9 * unit.to_base(n).abs(), "{} is {}", s, long);
+    }
+    assert_eq!(si.unit_from_str("watt-hour per second"), si.unit_from_str("Wh/s"));
+    assert_eq!(si.unit_from_str("kilowatt-hours"), si.unit_from_str("kWh"));
+    assert_eq!(si.unit_from_str("degrees Celsius"), si.unit_from_str("°C"));
 }
@@ -0,0 +1,243 @@
+use std::fmt::{self, Display};
+use std::error::Error;
+use std::ops::Range;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An error from doing maths with or reading units
+pub enum UnitError {
+    /// The dimensions of the two quantities were supposed to be the same
+    DimensionMismatch(Dimension, Dimension),
+    /// Tried to add two absolute values or to subtract an absolute value from a difference
+    AbsoluteAddition,
+    /// Tried to add a level in a logarithmic unit to something other than a gain
+    LogarithmicAddition,
+    /// Taking the root of this degree would give a dimension with fractional exponents
+    FractionalRoot(Dimension, i16),
+    /// Tried to take the zeroth root, which doesn't exist
+    ZeroRoot,
+    /// An exponent of the resulting dimension is too large
+    ExponentOverflow,
+    /// A unit or value couldn't be read
+    Parse(ParseError),
+}
+
+impl Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnitError::DimensionMismatch(ref a, ref b) => write!(f, "Mismatched dimensions {:#} and {:#}", a, b),
+            UnitError::AbsoluteAddition => write!(f, "Absolute values can only be added to or subtracted by differences"),
+            UnitError::LogarithmicAddition => write!(f, "Levels in logarithmic units can only be added to or subtracted by gains"),
+            UnitError::FractionalRoot(ref dim, n) => write!(f, "Root {} of {:#} would have fractional exponents", n, dim),
+            UnitError::ZeroRoot => write!(f, "There is no zeroth root"),
+            UnitError::ExponentOverflow => write!(f, "Exponent of dimension too large"),
+            UnitError::Parse(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for UnitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            UnitError::Parse(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for UnitError {
+    fn from(e: ParseError) -> Self {
+        UnitError::Parse(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An error from reading a unit or a value
+///
+/// Each variant carries the offending part of the input and its byte span in the input
+pub enum ParseError {
+    /// A symbol that isn't a unit in the system
+    UnknownUnit {
+        /// The unknown symbol
+        name: String,
+        /// Where the symbol is in the input
+        span: Range<usize>,
+        /// Units in the system with a similar name
+        suggestions: Vec<String>,
+    },
+    /// A number that couldn't be read
+    InvalidNumber {
+        /// The malformed number
+        text: String,
+        /// Where the number is in the input
+        span: Range<usize>,
+    },
+    /// An exponent that isn't an integer, a fraction like `-1/2` or a decimal number with
+    /// at most four decimals like `0.5`, or whose reduced fraction doesn't fit in an `i16`
+    InvalidExponent {
+        /// The malformed exponent
+        text: String,
+        /// Where the exponent is in the input
+        span: Range<usize>,
+    },
+    /// A character that can't be at this place
+    Unexpected {
+        /// The unexpected character
+        text: String,
+        /// Where the character is in the input
+        span: Range<usize>,
+    },
+    /// A part of a compound value (like the `3 in` of `5 ft 3 in`) that can't be added to the parts before it,
+    /// because it has another dimension or both are absolute
+    InvalidCompound {
+        /// The part of the compound value
+        text: String,
+        /// Where the part is in the input
+        span: Range<usize>,
+    },
+    /// A unit whose dimension has an exponent that is too large, like `m^20000 m^20000`
+    ExponentOverflow {
+        /// The unit
+        text: String,
+        /// Where the unit is in the input
+        span: Range<usize>,
+    },
+    /// The input ended where more was expected (e.g. a closing parenthesis)
+    UnexpectedEnd {
+        /// The empty span at the end of the input
+        span: Range<usize>,
+    },
+}
+
+impl ParseError {
+    /// The byte span of the offending part of the input
+    pub fn span(&self) -> Range<usize> {
+        match *self {
+            ParseError::UnknownUnit{ref span, ..} |
+            ParseError::InvalidNumber{ref span, ..} |
+            ParseError::InvalidExponent{ref span, ..} |
+            ParseError::Unexpected{ref span, ..} |
+            ParseError::InvalidCompound{ref span, ..} |
+            ParseError::ExponentOverflow{ref span, ..} |
+            ParseError::UnexpectedEnd{ref span} => span.clone(),
+        }
+    }
+    /// The offending part of the input
+    pub fn text(&self) -> &str {
+        match *self {
+            ParseError::UnknownUnit{name: ref text, ..} |
+            ParseError::InvalidNumber{ref text, ..} |
+            ParseError::InvalidExponent{ref text, ..} |
+            ParseError::Unexpected{ref text, ..} |
+            ParseError::InvalidCompound{ref text, ..} |
+            ParseError::ExponentOverflow{ref text, ..} => text,
+            ParseError::UnexpectedEnd{..} => "",
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::UnknownUnit{ref name, ref suggestions, ..} => {
+                write!(f, "No such unit `{}'", name)?;
+                if !suggestions.is_empty() {
+                    write!(f, ", did you mean `{}'?", suggestions.join("', `"))?;
+                }
+                Ok(())
+            }
+            ParseError::InvalidNumber{ref text, ..} => write!(f, "Invalid number `{}'", text),
+            ParseError::InvalidExponent{ref text, ..} => write!(f, "Invalid exponent `{}'", text),
+            ParseError::Unexpected{ref text, ..} => write!(f, "Unexpected `{}'", text),
+            ParseError::InvalidCompound{ref text, ..} => write!(f, "`{}' can't be added to the value before it", text),
+            ParseError::ExponentOverflow{ref text, ..} => write!(f, "Exponent of dimension too large in `{}'", text),
+            ParseError::UnexpectedEnd{..} => write!(f, "Unexpected end of input"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An error from loading unit definitions
+///
+/// Line numbers start at 1
+pub enum DefinitionError {
+    /// A line that is neither a definition, a comment nor empty
+    Syntax {
+        /// The line number
+        line: usize,
+        /// The line
+        text: String,
+    },
+    /// A unit that is defined twice or already is in the system
+    Duplicate {
+        /// The line number of the second definition
+        line: usize,
+        /// The name of the unit
+        name: String,
+    },
+    /// Definitions that depend on each other in a cycle
+    Cycle {
+        /// The line number of the first definition in the cycle
+        line: usize,
+        /// The names in the cycle, starting and ending with the same one
+        names: Vec<String>,
+    },
+    /// The right-hand side of a definition couldn't be read
+    Parse {
+        /// The line number
+        line: usize,
+        /// The error, with spans relative to the right-hand side
+        error: ParseError,
+    },
+    /// A new base dimension, which can't be added since a `Dimension` only has the SI base dimensions
+    NewDimension {
+        /// The line number
+        line: usize,
+        /// The name of the dimension
+        name: String,
+    },
+    /// The definitions couldn't be read from a file
+    Io(String),
+}
+
+impl DefinitionError {
+    /// The line the error is on, if it is about a line
+    pub fn line(&self) -> Option<usize> {
+        match *self {
+            DefinitionError::Syntax{line, ..} |
+            DefinitionError::Duplicate{line, ..} |
+            DefinitionError::Cycle{line, ..} |
+            DefinitionError::Parse{line, ..} |
+            DefinitionError::NewDimension{line, ..} => Some(line),
+            DefinitionError::Io(_) => None,
+        }
+    }
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line() {
+            write!(f, "Line {}: ", line)?;
+        }
+        match *self {
+            DefinitionError::Syntax{ref text, ..} => write!(f, "Expected a definition like `name = value`, found `{}'", text),
+            DefinitionError::Duplicate{ref name, ..} => write!(f, "`{}' is already defined", name),
+            DefinitionError::Cycle{ref names, ..} => write!(f, "Definitions depend on each other: {}", names.join(" -> ")),
+            DefinitionError::Parse{ref error, ..} => error.fmt(f),
+            DefinitionError::NewDimension{ref name, ..} => write!(f, "Can't add the dimension `{}', only the SI base dimensions exist", name),
+            DefinitionError::Io(ref e) => write!(f, "Couldn't read definitions: {}", e),
+        }
+    }
+}
+
+impl Error for DefinitionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            DefinitionError::Parse{ref error, ..} => Some(error),
+            _ => None,
+        }
+    }
+}
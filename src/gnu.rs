@@ -0,0 +1,704 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+
+use num::Float;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Why an entry of a GNU units definitions file couldn't be imported
+pub enum Unmappable {
+    /// A primitive unit that isn't one of the SI base units, like `bit !`
+    Primitive,
+    /// Defined in terms of an entry that couldn't be imported
+    DependsOn(String),
+    /// Defined in terms of itself through the named entry
+    Cycle(String),
+    /// Uses a name that isn't defined in the file
+    Unknown(String),
+    /// A function that isn't a linear or affine scale, like `dB(x)`
+    Nonlinear,
+    /// A piecewise linear table, like `wiregauge[m]`
+    Table,
+    /// A name that `unit_from_str` can't read, like `US$`
+    Name,
+    /// Defined earlier in the file, which is the definition kept
+    Redefined,
+    /// A directive that isn't supported, like `!include` or `!unitlist`
+    Directive,
+    /// A definition that couldn't be read or evaluated
+    Invalid(String),
+}
+
+impl Display for Unmappable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Unmappable::*;
+        match *self {
+            Primitive => write!(f, "is a primitive unit that isn't an SI base unit"),
+            DependsOn(ref name) => write!(f, "depends on `{}', which couldn't be imported", name),
+            Cycle(ref name) => write!(f, "is defined in terms of itself through `{}'", name),
+            Unknown(ref name) => write!(f, "uses `{}', which isn't defined", name),
+            Nonlinear => write!(f, "is a function that isn't a linear or affine scale"),
+            Table => write!(f, "is a piecewise linear table"),
+            Name => write!(f, "has a name that can't be read as a unit"),
+            Redefined => write!(f, "is already defined"),
+            Directive => write!(f, "is an unsupported directive"),
+            Invalid(ref msg) => write!(f, "is invalid: {}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An entry of a GNU units definitions file that couldn't be imported
+pub struct Unmapped {
+    /// The line the entry starts on
+    pub line: usize,
+    /// The name of the entry, like `bit`, `kilo-`, `tempF(x)` or `!include`
+    pub name: String,
+    /// Why it wasn't imported
+    pub reason: Unmappable,
+}
+
+impl Display for Unmapped {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: `{}' {}", self.line, self.name, self.reason)
+    }
+}
+
+/// A value in SI base units
+type Base<N> = (N, Dimension);
+/// The input unit of a function and the unit its results are in
+type Scale<N> = (Base<N>, Unit<N>);
+/// The parameter of the function being evaluated and its value
+type Param<'a, N> = Option<(&'a str, Base<N>)>;
+
+/// A function definition like `tempF(x) units=[1;K] (x+(-32)) degF + stdtemp ; ...`
+struct Function<'a> {
+    param: &'a str,
+    input: &'a str,
+    forward: &'a str,
+}
+
+/// The reason for failing to use the entry `name` that failed with `e`
+fn depends_on(e: Unmappable, name: &str) -> Unmappable {
+    match e {
+        Unmappable::Cycle(n) => Unmappable::Cycle(n),
+        _ => Unmappable::DependsOn(name.to_owned()),
+    }
+}
+
+/// Characters that end a name in an expression
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "+-*/|^()[];,".contains(c)
+}
+
+/// Whether `s` starts with the word `per`, which divides like `/`
+fn starts_with_per(s: &str) -> bool {
+    s.strip_prefix("per").is_some_and(|rest| rest.chars().next().is_none_or(is_delimiter))
+}
+
+fn skip_whitespace(s: &mut &str) {
+    *s = s.trim_start();
+}
+
+fn pow<N: Float>((x, dim): Base<N>, e: Base<N>) -> Result<Base<N>, Unmappable> {
+    if e.1 != NUL {
+        return Err(Unmappable::Invalid("exponent with a dimension".to_owned()))
+    }
+    if dim == NUL {
+        return Ok((x.powf(e.0), NUL))
+    }
+    let exp = Exponent::approximate(e.0).ok_or_else(|| Unmappable::Invalid("irrational power of a unit".to_owned()))?;
+    Ok((x.powf(e.0), dim * exp))
+}
+
+/// Resolves the entries of a definitions file on demand, in any order
+struct Importer<'a, N: Float> {
+    units: HashMap<&'a str, &'a str>,
+    /// Definitions of prefixes by their names without the `-`
+    prefixes: HashMap<&'a str, (&'a str, &'a str)>,
+    functions: HashMap<&'a str, Function<'a>>,
+    resolved: HashMap<&'a str, Result<Base<N>, Unmappable>>,
+    resolved_prefixes: HashMap<&'a str, Result<Base<N>, Unmappable>>,
+    resolved_functions: HashMap<&'a str, Result<Scale<N>, Unmappable>>,
+    /// The entries being resolved, with prefixes written with their `-`
+    stack: Vec<&'a str>,
+}
+
+impl<'a, N: Float> Importer<'a, N> {
+    fn resolve(&mut self, name: &'a str) -> Result<Base<N>, Unmappable> {
+        if let Some(result) = self.resolved.get(name) {
+            return result.clone()
+        }
+        if self.stack.contains(&name) {
+            return Err(Unmappable::Cycle(name.to_owned()))
+        }
+        self.stack.push(name);
+        let rhs = self.units[name];
+        let result = match rhs.strip_prefix('!') {
+            Some("dimensionless") => Ok((N::one(), NUL)),
+            Some(_) => match name {
+                _ if name == SI.length => Ok((N::one(), LENGTH)),
+                _ if name == SI.time => Ok((N::one(), TIME)),
+                _ if name == SI.mass => Ok((N::one(), MASS)),
+                _ if name == SI.current => Ok((N::one(), CURRENT)),
+                _ if name == SI.temperature => Ok((N::one(), TEMPERATURE)),
+                _ if name == SI.substance_amount => Ok((N::one(), AMOUNT_OF_SUBSTANCE)),
+                _ if name == SI.luminous_intensity => Ok((N::one(), LUMINOUS_INTENSITY)),
+                _ => Err(Unmappable::Primitive),
+            },
+            None => self.evaluate(rhs, None),
+        };
+        self.stack.pop();
+        self.resolved.insert(name, result.clone());
+        result
+    }
+    fn resolve_prefix(&mut self, name: &'a str) -> Result<Base<N>, Unmappable> {
+        if let Some(result) = self.resolved_prefixes.get(name) {
+            return result.clone()
+        }
+        let (full_name, rhs) = self.prefixes[name];
+        if self.stack.contains(&full_name) {
+            return Err(Unmappable::Cycle(full_name.to_owned()))
+        }
+        self.stack.push(full_name);
+        let result = self.evaluate(rhs, None);
+        self.stack.pop();
+        self.resolved_prefixes.insert(name, result.clone());
+        result
+    }
+    /// Resolves a function into its input unit and the unit its results are in
+    fn resolve_function(&mut self, name: &'a str) -> Result<Scale<N>, Unmappable> {
+        if let Some(result) = self.resolved_functions.get(name) {
+            return result.clone()
+        }
+        if self.stack.contains(&name) {
+            return Err(Unmappable::Cycle(name.to_owned()))
+        }
+        self.stack.push(name);
+        let (param, input, forward) = {
+            let function = &self.functions[name];
+            (function.param, function.input, function.forward)
+        };
+        let result = self.evaluate(input, None).and_then(|input| {
+            let at = |n: f64| Some((param, (input.0 * N::from(n).unwrap(), input.1)));
+            let (f0, f1, f2) = (self.evaluate(forward, at(0.))?, self.evaluate(forward, at(1.))?, self.evaluate(forward, at(2.))?);
+            let (offset, slope) = (f0.0, f1.0 - f0.0);
+            let linear = (f2.0 - f1.0 - slope).abs() <= N::from(1e-9).unwrap() * slope.abs();
+            if !linear || slope == N::zero() || !slope.is_finite() || f0.1 != f1.1 || f1.1 != f2.1 {
+                return Err(Unmappable::Nonlinear)
+            }
+            let unit = if offset == N::zero() {
+                Unit::with_factor(f0.1, slope)
+            } else {
+                Unit::with_offset(f0.1, slope, offset)
+            };
+            Ok((input, unit))
+        });
+        self.stack.pop();
+        self.resolved_functions.insert(name, result.clone());
+        result
+    }
+    /// Looks up a name like GNU units does: as a unit, a prefix, the plural of a unit
+    /// or a prefix followed by a unit
+    fn lookup(&mut self, name: &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        match param {
+            Some((p, value)) if p == name => return Ok(value),
+            _ => (),
+        }
+        if let Some((&name, _)) = self.units.get_key_value(name) {
+            return self.resolve(name).map_err(|e| depends_on(e, name))
+        }
+        if let Some((&name, _)) = self.prefixes.get_key_value(name) {
+            return self.resolve_prefix(name).map_err(|e| depends_on(e, name))
+        }
+        if self.functions.contains_key(name) {
+            return Err(Unmappable::Invalid(format!("the function `{}' is used without an argument", name)))
+        }
+        if let Some(unit) = self.singular(name) {
+            return self.resolve(unit).map_err(|e| depends_on(e, unit))
+        }
+        let mut prefixes: Vec<_> = self.prefixes.keys()
+            .cloned()
+            .filter(|p| name.len() > p.len() && name.starts_with(p))
+            .collect();
+        prefixes.sort_by_key(|p| usize::MAX - p.len());
+        for prefix in prefixes {
+            let rest = &name[prefix.len()..];
+            let unit = self.units.get_key_value(rest).map(|(&n, _)| n).or_else(|| self.singular(rest));
+            if let Some(unit) = unit {
+                let (factor, dim) = self.resolve_prefix(prefix).map_err(|e| depends_on(e, prefix))?;
+                let (x, unit_dim) = self.resolve(unit).map_err(|e| depends_on(e, unit))?;
+                return Ok((factor * x, dim + unit_dim))
+            }
+        }
+        Err(Unmappable::Unknown(name.to_owned()))
+    }
+    /// The unit `name` is the plural of
+    fn singular(&self, name: &'a str) -> Option<&'a str> {
+        let stems = [name.strip_suffix('s'), name.strip_suffix("es")];
+        let unit = stems.iter()
+            .filter_map(|&s| s)
+            .filter(|s| s.chars().count() > 1)
+            .filter_map(|s| self.units.get_key_value(s))
+            .map(|(&n, _)| n)
+            .next();
+        unit.or_else(|| {
+            let stem = name.strip_suffix("ies")?;
+            self.units.keys().cloned().find(|n| n.strip_suffix('y') == Some(stem))
+        })
+    }
+    fn evaluate(&mut self, expr: &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        let mut s = expr;
+        let value = self.sum(&mut s, param)?;
+        skip_whitespace(&mut s);
+        if !s.is_empty() {
+            return Err(Unmappable::Invalid(format!("unexpected `{}'", s)))
+        }
+        Ok(value)
+    }
+    fn sum(&mut self, s: &mut &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        let mut acc = self.product(s, param)?;
+        loop {
+            skip_whitespace(s);
+            let sign = match s.chars().next() {
+                Some('+') => N::one(),
+                Some('-') => -N::one(),
+                _ => return Ok(acc),
+            };
+            *s = &s[1..];
+            let rhs = self.product(s, param)?;
+            if rhs.1 != acc.1 {
+                return Err(Unmappable::Invalid("sum of different dimensions".to_owned()))
+            }
+            acc.0 = acc.0 + sign * rhs.0;
+        }
+    }
+    /// Multiplication with `*` and division with `/` or `per`, which bind looser than juxtaposition
+    fn product(&mut self, s: &mut &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        let mut acc = self.juxtaposition(s, param)?;
+        loop {
+            skip_whitespace(s);
+            let divide = if s.starts_with('/') {
+                *s = &s[1..];
+                true
+            } else if starts_with_per(s) {
+                *s = &s[3..];
+                true
+            } else if s.starts_with('*') && !s.starts_with("**") {
+                *s = &s[1..];
+                false
+            } else {
+                return Ok(acc)
+            };
+            let (x, dim) = self.juxtaposition(s, param)?;
+            acc = if divide { (acc.0 / x, acc.1 - dim) } else { (acc.0 * x, acc.1 + dim) };
+        }
+    }
+    fn juxtaposition(&mut self, s: &mut &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        let mut acc = self.unary(s, param)?;
+        loop {
+            skip_whitespace(s);
+            let end = s.chars().next().is_none_or(|c| "+-*/|^)];,".contains(c));
+            if end || (starts_with_per(s)) {
+                return Ok(acc)
+            }
+            let (x, dim) = self.unary(s, param)?;
+            acc = (acc.0 * x, acc.1 + dim);
+        }
+    }
+    fn unary(&mut self, s: &mut &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        skip_whitespace(s);
+        if s.starts_with('-') {
+            *s = &s[1..];
+            return self.unary(s, param).map(|(x, dim)| (-x, dim))
+        }
+        let base = self.primary(s, param)?;
+        skip_whitespace(s);
+        let op_len = if s.starts_with("**") { 2 } else if s.starts_with('^') { 1 } else { 0 };
+        if op_len == 0 {
+            return Ok(base)
+        }
+        *s = &s[op_len..];
+        let exp = self.unary(s, param)?;
+        pow(base, exp)
+    }
+    fn primary(&mut self, s: &mut &'a str, param: Param<'a, N>) -> Result<Base<N>, Unmappable> {
+        skip_whitespace(s);
+        match s.chars().next() {
+            None => Err(Unmappable::Invalid("missing a unit or number".to_owned())),
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut x = number(s)?;
+                if s.starts_with('|') {
+                    *s = &s[1..];
+                    x = x / number(s)?;
+                }
+                Ok((x, NUL))
+            }
+            Some('(') => {
+                *s = &s[1..];
+                let value = self.sum(s, param)?;
+                skip_whitespace(s);
+                if !s.starts_with(')') {
+                    return Err(Unmappable::Invalid("missing `)'".to_owned()))
+                }
+                *s = &s[1..];
+                Ok(value)
+            }
+            Some(c) if is_delimiter(c) => Err(Unmappable::Invalid(format!("unexpected `{}'", c))),
+            Some(_) => {
+                let end = s.find(is_delimiter).unwrap_or(s.len());
+                let token = &s[..end];
+                *s = &s[end..];
+                if s.starts_with('(') {
+                    *s = &s[1..];
+                    let arg = self.sum(s, param)?;
+                    skip_whitespace(s);
+                    if !s.starts_with(')') {
+                        return Err(Unmappable::Invalid("missing `)'".to_owned()))
+                    }
+                    *s = &s[1..];
+                    return self.call(token, arg)
+                }
+                // A name ending in a digit is raised to that power, unless the digit follows an `_`
+                let digits = token.len() - token.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+                let (name, exp) = match token[..token.len()-digits].chars().last() {
+                    Some(c) if digits > 0 && c != '_' => token.split_at(token.len()-digits),
+                    _ => (token, ""),
+                };
+                let value = self.lookup(name, param)?;
+                match exp.parse::<i16>() {
+                    Ok(exp) => pow(value, (N::from(exp).unwrap(), NUL)),
+                    Err(_) => Ok(value),
+                }
+            }
+        }
+    }
+    fn call(&mut self, name: &'a str, (x, dim): Base<N>) -> Result<Base<N>, Unmappable> {
+        let dimensionless = |f: fn(N) -> N| if dim == NUL {
+            Ok((f(x), NUL))
+        } else {
+            Err(Unmappable::Invalid(format!("`{}' of a value with a dimension", name)))
+        };
+        match name {
+            "sqrt" => pow((x, dim), (N::from(0.5).unwrap(), NUL)),
+            "cuberoot" => Ok((x.cbrt(), dim * Exponent::new(1, 3))),
+            "exp" => dimensionless(N::exp),
+            "ln" => dimensionless(N::ln),
+            "log" => dimensionless(N::log10),
+            "log2" => dimensionless(N::log2),
+            "sin" => dimensionless(N::sin),
+            "cos" => dimensionless(N::cos),
+            "tan" => dimensionless(N::tan),
+            _ => {
+                let name = match self.functions.get_key_value(name) {
+                    Some((&name, _)) => name,
+                    None => return Err(Unmappable::Unknown(name.to_owned())),
+                };
+                let (input, unit) = self.resolve_function(name)
+                    .map_err(|_| Unmappable::DependsOn(name.to_owned()))?;
+                if dim != input.1 {
+                    return Err(Unmappable::Invalid(format!("argument of `{}' with the wrong dimension", name)))
+                }
+                Ok((unit.to_base(x / input.0), unit.dimension))
+            }
+        }
+    }
+}
+
+/// Inserts `value` unless `key` is already there, returning whether it was inserted
+fn insert_new<'a, V>(map: &mut HashMap<&'a str, V>, key: &'a str, value: V) -> bool {
+    match map.entry(key) {
+        Entry::Occupied(_) => false,
+        Entry::Vacant(e) => {
+            e.insert(value);
+            true
+        }
+    }
+}
+
+/// Reads a number like `1`, `.5` or `6.022e23`
+fn number<N: Float>(s: &mut &str) -> Result<N, Unmappable> {
+    let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let mut end = digits(s);
+    if s[end..].starts_with('.') {
+        end += 1 + digits(&s[end+1..]);
+    }
+    if s[end..].starts_with(['e', 'E']) {
+        let sign = if s[end+1..].starts_with(['+', '-']) { 1 } else { 0 };
+        let exp_digits = digits(&s[end+1+sign..]);
+        if exp_digits > 0 {
+            end += 1 + sign + exp_digits;
+        }
+    }
+    let x: f64 = s[..end].parse().map_err(|_| Unmappable::Invalid(format!("bad number `{}'", &s[..end])))?;
+    *s = &s[end..];
+    Ok(N::from(x).unwrap())
+}
+
+/// Joins continued lines, strips comments and applies the directives of a definitions file
+///
+/// Returns the lines to read with their line numbers
+fn preprocess(defs: &str, locale: &str, unmapped: &mut Vec<Unmapped>) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut continued: Option<(usize, String)> = None;
+    for (i, line) in defs.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let (start, mut text) = continued.take().unwrap_or((i + 1, String::new()));
+        match line.trim_end().strip_suffix('\\') {
+            Some(line) => {
+                text.push_str(line);
+                text.push(' ');
+                continued = Some((start, text));
+            }
+            None => {
+                text.push_str(line);
+                lines.push((start, text));
+            }
+        }
+    }
+    lines.extend(continued);
+
+    let mut vars = HashMap::new();
+    let mut conditions: Vec<bool> = Vec::new();
+    let mut ret = Vec::new();
+    for (line, text) in &lines {
+        let (line, text) = (*line, text.trim());
+        if text.is_empty() {
+            continue
+        }
+        let active = conditions.iter().all(|&c| c);
+        if !text.starts_with('!') {
+            if active {
+                ret.push((line, text.to_owned()));
+            }
+            continue
+        }
+        let mut words = text.split_whitespace();
+        let directive = words.next().unwrap();
+        let args: Vec<_> = words.collect();
+        match directive {
+            "!locale" => conditions.push(args.first() == Some(&locale)),
+            "!utf8" => conditions.push(true),
+            "!var" | "!varnot" => {
+                let matches = args.split_first()
+                    .and_then(|(var, values)| vars.get(var).map(|v| values.contains(v)))
+                    .unwrap_or(false);
+                conditions.push(matches == (directive == "!var"));
+            }
+            "!endlocale" | "!endutf8" | "!endvar" => {
+                conditions.pop();
+            }
+            "!set" if active => {
+                if let [var, value, ..] = args[..] {
+                    vars.entry(var).or_insert(value);
+                }
+            }
+            "!message" | "!prompt" | "!set" => (),
+            _ if active => unmapped.push(Unmapped {line, name: directive.to_owned(), reason: Unmappable::Directive}),
+            _ => (),
+        }
+    }
+    ret
+}
+
+impl<N: Float> UnitSystem<N> {
+    /// Imports a definitions file of GNU units, like its `definitions.units`
+    ///
+    /// Units and the function definitions that are affine scales (like `tempF(x)`) are added
+    /// to a system with the SI base units. Prefixes like `kilo-` and plurals are used for reading
+    /// the definitions, but the system only has the SI prefixes on the units that take them in `si()`.
+    /// `!locale` blocks are read if they match `locale`, like `"en_GB"`.
+    ///
+    /// Also returns the entries that couldn't be imported, like units with dimensions outside
+    /// the seven SI base dimensions and everything defined in terms of them
+    pub fn from_gnu_units(defs: &str, locale: &str) -> (Self, Vec<Unmapped>) {
+        let mut unmapped = Vec::new();
+        let lines = preprocess(defs, locale, &mut unmapped);
+
+        let mut importer = Importer {
+            units: HashMap::new(),
+            prefixes: HashMap::new(),
+            functions: HashMap::new(),
+            resolved: HashMap::new(),
+            resolved_prefixes: HashMap::new(),
+            resolved_functions: HashMap::new(),
+            stack: Vec::new(),
+        };
+        let mut entries = Vec::new();
+        for &(line, ref text) in &lines {
+            let (name, rhs) = match text.find(char::is_whitespace) {
+                Some(i) => (&text[..i], text[i..].trim()),
+                None => (&text[..], ""),
+            };
+            let mut report = |reason| unmapped.push(Unmapped {line, name: name.to_owned(), reason});
+            if name.contains('[') {
+                report(Unmappable::Table);
+                continue
+            }
+            if rhs.is_empty() {
+                report(Unmappable::Invalid("missing definition".to_owned()));
+                continue
+            }
+            let new = if let Some(i) = name.find('(') {
+                let function = &name[..i];
+                let param = name[i+1..].trim_end_matches(')');
+                let mut input = "1";
+                let mut forward = rhs;
+                while let Some(option) = ["units=", "domain=", "range=", "noerror"].iter().find(|o| forward.starts_with(*o)) {
+                    let end = forward.find(char::is_whitespace).unwrap_or(forward.len());
+                    if *option == "units=" {
+                        input = forward[..end].trim_start_matches("units=[").split(';').next().unwrap();
+                    }
+                    forward = forward[end..].trim_start();
+                }
+                let forward = forward.split(';').next().unwrap();
+                insert_new(&mut importer.functions, function, Function {param, input, forward})
+            } else if let Some(prefix) = name.strip_suffix('-').filter(|p| !p.is_empty()) {
+                insert_new(&mut importer.prefixes, prefix, (name, rhs))
+            } else {
+                insert_new(&mut importer.units, name, rhs)
+            };
+            if new {
+                entries.push((line, name));
+            } else {
+                report(Unmappable::Redefined);
+            }
+        }
+        let mut sys = Self::new_base_with_cap(SI, importer.units.len());
+        for (line, name) in entries {
+            let result = if let Some(function) = name.find('(').map(|i| &name[..i]) {
+                importer.resolve_function(function).map(|(_, unit)| (function, unit))
+            } else if let Some(prefix) = name.strip_suffix('-').filter(|p| !p.is_empty()) {
+                importer.resolve_prefix(prefix).map(|_| ("", Unit::new(NUL)))
+            } else {
+                importer.resolve(name).map(|(x, dim)| (name, Unit::with_factor(dim, x)))
+            };
+            let reason = match result {
+                Ok(("", _)) => continue,
+                Ok((name, unit)) if name.chars().all(is_unit_char) => {
+                    sys.add_unit(name.to_owned(), unit);
+                    continue
+                }
+                Ok(_) => Unmappable::Name,
+                Err(reason) => reason,
+            };
+            unmapped.push(Unmapped {line, name: name.to_owned(), reason});
+        }
+        unmapped.sort_by_key(|u| u.line);
+
+        for name in Self::si().prefixable {
+            if sys.units.contains_key(&name) {
+                sys.prefixable.insert(name);
+            }
+        }
+        (sys, unmapped)
+    }
+    /// Imports the GNU units definitions file at `path`, see `from_gnu_units`
+    pub fn from_gnu_units_file<P: AsRef<Path>>(path: P, locale: &str) -> Result<(Self, Vec<Unmapped>), DefinitionError> {
+        let defs = fs::read_to_string(path).map_err(|e| DefinitionError::Io(e.to_string()))?;
+        Ok(Self::from_gnu_units(&defs, locale))
+    }
+}
+
+#[test]
+fn gnu_units_test() {
+    let defs = r#"
+# Primitives
+m          !
+s          !
+kg         !
+K          !
+radian     !dimensionless
+bit        !
+US$        !
+
+kilo-      1e3
+k-         kilo
+milli-     1e-3
+centi-     0.01
+c          299792458 m/s   # The speed of light, not centi
+gram       millikg
+g          gram
+minute     60 s
+min        minute
+hour       60 min
+meter      m
+inch       2.54 centimeters
+foot       12 inches
+ft         foot
+mph        mile/hour
+mile       5280 ft
+acre       43560 ft2
+J          kg m^2 / s^2
+third      1|3
+hertz      1 per s
+stdtemp    273.15 K
+degF       5|9 K
+tempF(x) units=[1;K] domain=[-459.67,) range=[0,) \
+           (x+(-32)) degF + stdtemp ; (tempF+(-stdtemp))/degF + 32
+dB(x) units=[1;1] 10^(x/10) ; 10 log(dB)
+wiregauge[m] 0 0.008 10 0.0026
+byte       8 bit
+dollar     US$
+loop       2 loop
+mystery    3 unknownunit
+g          2 kg
+!locale en_GB
+ton        2240 lb
+lb         0.45359237 kg
+!endlocale
+!locale en_US
+ton        2000 lb
+lb         0.45359237 kg
+!endlocale
+!set UNITS_ENGLISH US
+!var UNITS_ENGLISH GB
+gallon     4.54609e-3 m^3
+!endvar
+!var UNITS_ENGLISH US
+gallon     231 inch^3
+!endvar
+!include currency.units
+"#;
+    let (sys, unmapped) = UnitSystem::<f64>::from_gnu_units(defs, "en_GB");
+    let close = |name: &str, factor: f64| {
+        let unit = sys.get_unit(name).unwrap_or_else(|| panic!("no {}", name));
+        assert!((unit.factor - factor).abs() <= 1e-12 * factor, "{} is {}", name, unit.factor);
+    };
+    close("g", 1e-3);
+    close("kg", 1.);
+    close("foot", 0.3048);
+    close("mph", 0.44704);
+    close("acre", 4046.8564224);
+    close("third", 1. / 3.);
+    close("c", 299792458.);
+    close("ton", 1016.0469088);
+    close("gallon", 0.003785411784);
+    assert_eq!(sys.get_unit("J").unwrap().dimension, ENERGY);
+    assert_eq!(sys.get_unit("hertz").unwrap().dimension, FREQUENCY);
+    assert_eq!(sys.get_unit("radian").unwrap().dimension, NUL);
+    assert_eq!(sys.get_unit("rad"), None);
+    let freezing = sys.val(32., "tempF").unwrap().absolute();
+    assert!((freezing.0 - 273.15).abs() < 1e-9 && freezing.1 == Unit::new(TEMPERATURE));
+    assert_eq!(sys.get_unit("km").unwrap().factor, 1e3);
+    assert_eq!(sys.get_unit("kgram"), None);
+
+    let reasons: Vec<_> = unmapped.iter().map(|u| (u.name.as_str(), u.reason.clone())).collect();
+    assert_eq!(reasons, vec![
+        ("bit", Unmappable::Primitive),
+        ("US$", Unmappable::Primitive),
+        ("dB(x)", Unmappable::Nonlinear),
+        ("wiregauge[m]", Unmappable::Table),
+        ("byte", Unmappable::DependsOn("bit".to_owned())),
+        ("dollar", Unmappable::DependsOn("US$".to_owned())),
+        ("loop", Unmappable::Cycle("loop".to_owned())),
+        ("mystery", Unmappable::Unknown("unknownunit".to_owned())),
+        ("g", Unmappable::Redefined),
+        ("!include", Unmappable::Directive),
+    ]);
+    assert_eq!(unmapped[0].to_string(), "Line 8: `bit' is a primitive unit that isn't an SI base unit");
+}
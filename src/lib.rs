@@ -2,6 +2,11 @@
 //! Crate for doing maths with units
 
 extern crate num;
+extern crate typenum;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 fn num_to_super(c: char) -> char {
     match c {
@@ -16,6 +21,7 @@ fn num_to_super(c: char) -> char {
         '7' => '⁷',
         '8' => '⁸',
         '9' => '⁹',
+        '/' => 'ᐟ',
         c => c
     }
 }
@@ -33,6 +39,7 @@ fn super_to_num(c: char) -> char {
         '⁷' => '7',
         '⁸' => '8',
         '⁹' => '9',
+        'ᐟ' => '/',
         c => c
     }
 }
@@ -57,11 +64,38 @@ fn super_test() {
 mod dimensions;
 pub use dimensions::*;
 
+mod error;
+pub use error::*;
+
+mod prefix;
+pub use prefix::*;
+
 mod units;
 pub use units::*;
 
+mod quantity;
+pub use quantity::*;
+
+mod uncertain;
+pub use uncertain::*;
+
+mod maths;
+
+pub mod constants;
+
 mod display;
-pub use display::UnitDisplay;
+pub use display::{UnitDisplay, PrefixOptions, UncertaintyNotation};
 
 mod read;
 use read::*;
+pub use read::is_unit_char;
+
+mod definitions;
+
+mod gnu;
+pub use gnu::*;
+
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+pub use serialize::*;
@@ -0,0 +1,171 @@
+use super::*;
+
+use num::Float;
+
+impl<N: Float> Value<N> {
+    /// The number in base units, failing if the value isn't dimensionless
+    fn dimensionless(self) -> Result<N, UnitError> {
+        if self.1.dimension != NUL {
+            return Err(UnitError::DimensionMismatch(self.1.dimension, NUL))
+        }
+        Ok(self.1.to_base(self.0))
+    }
+    /// The angle in radians, failing if the value isn't an angle
+    ///
+    /// Angles are either dimensionless or of the dimension `ANGLE`,
+    /// depending on whether the system keeps track of them
+    fn radians(self) -> Result<N, UnitError> {
+        if self.1.dimension == ANGLE {
+            return Ok(self.1.to_base(self.0))
+        }
+        self.dimensionless().map_err(|_| UnitError::DimensionMismatch(self.1.dimension, ANGLE))
+    }
+    /// Both values in the unit of `self` after making them absolute,
+    /// failing if they don't have the same dimension
+    fn same_unit(self, rhs: Self) -> Result<(N, N, Unit<N>), UnitError> {
+        let (Value(a, unit), Value(b, rhs_unit)) = (self.absolute(), rhs.absolute());
+        if unit.dimension != rhs_unit.dimension {
+            return Err(UnitError::DimensionMismatch(unit.dimension, rhs_unit.dimension))
+        }
+        Ok((a, rhs_unit.convert(b, &unit), unit))
+    }
+
+    /// Raises the value to the power `e`, which multiplies the exponents of its dimension by `e`
+    ///
+    /// Values in affine or logarithmic units are made absolute first.
+    /// Fails if an exponent of the dimension overflows
+    pub fn pow(self, e: Exponent) -> Result<Self, UnitError> {
+        let Value(n, unit) = self.absolute();
+        Ok(Value(e.pow(n), unit.checked_mul(e).ok_or(UnitError::ExponentOverflow)?))
+    }
+    /// Raises the value to the whole power `n`, failing if an exponent of the dimension overflows
+    pub fn powi(self, n: i16) -> Result<Self, UnitError> {
+        self.pow(Exponent::integer(n))
+    }
+    /// Takes the `n`th root, failing if `n` is zero or `i16::MIN`, whose reciprocal doesn't fit in an `Exponent`,
+    /// or if it would give a dimension with fractional exponents, like the square root of a length
+    pub fn root(self, n: i16) -> Result<Self, UnitError> {
+        if n == 0 {
+            return Err(UnitError::ZeroRoot)
+        }
+        let e = Exponent::checked_new(1, n).ok_or(UnitError::ExponentOverflow)?;
+        // A fractional exponent divided by `n` is never whole, even if it doesn't fit
+        if !self.1.dimension.checked_mul(e).is_some_and(|d| d.is_integral()) {
+            return Err(UnitError::FractionalRoot(self.1.dimension, n))
+        }
+        self.pow(e)
+    }
+    /// Takes the square root, which halves the exponents of the dimension
+    ///
+    /// Fails if any of them are odd
+    pub fn sqrt(self) -> Result<Self, UnitError> {
+        self.root(2)
+    }
+    /// Takes the cube root, failing if any exponent of the dimension isn't a multiple of three
+    pub fn cbrt(self) -> Result<Self, UnitError> {
+        self.root(3)
+    }
+    /// The absolute value, in the same unit unless it's affine or logarithmic
+    pub fn abs(self) -> Self {
+        let Value(n, unit) = self.absolute();
+        Value(n.abs(), unit)
+    }
+    /// Raises e to the power of a dimensionless value
+    pub fn exp(self) -> Result<Self, UnitError> {
+        Ok(Value(self.dimensionless()?.exp(), Unit::new(NUL)))
+    }
+    /// The natural logarithm of a dimensionless value
+    pub fn ln(self) -> Result<Self, UnitError> {
+        Ok(Value(self.dimensionless()?.ln(), Unit::new(NUL)))
+    }
+    /// The base 10 logarithm of a dimensionless value
+    pub fn log10(self) -> Result<Self, UnitError> {
+        Ok(Value(self.dimensionless()?.log10(), Unit::new(NUL)))
+    }
+    /// The sine of an angle
+    pub fn sin(self) -> Result<Self, UnitError> {
+        Ok(Value(self.radians()?.sin(), Unit::new(NUL)))
+    }
+    /// The cosine of an angle
+    pub fn cos(self) -> Result<Self, UnitError> {
+        Ok(Value(self.radians()?.cos(), Unit::new(NUL)))
+    }
+    /// The tangent of an angle
+    pub fn tan(self) -> Result<Self, UnitError> {
+        Ok(Value(self.radians()?.tan(), Unit::new(NUL)))
+    }
+    /// The angle in radians whose sine is this dimensionless value
+    ///
+    /// The angle has the dimension `angle`, which is `ANGLE` in systems keeping track of angles
+    /// and `NUL` otherwise (see `UnitSystem::angle`)
+    pub fn asin(self, angle: Dimension) -> Result<Self, UnitError> {
+        Ok(Value(self.dimensionless()?.asin(), Unit::new(angle)))
+    }
+    /// The angle in radians of the dimension `angle` whose cosine is this dimensionless value
+    pub fn acos(self, angle: Dimension) -> Result<Self, UnitError> {
+        Ok(Value(self.dimensionless()?.acos(), Unit::new(angle)))
+    }
+    /// The angle in radians of the dimension `angle` whose tangent is this dimensionless value
+    pub fn atan(self, angle: Dimension) -> Result<Self, UnitError> {
+        Ok(Value(self.dimensionless()?.atan(), Unit::new(angle)))
+    }
+    /// The angle in radians of the dimension `angle` of the point (`x`, `self`),
+    /// failing if they don't have the same dimension
+    pub fn atan2(self, x: Self, angle: Dimension) -> Result<Self, UnitError> {
+        let (y, x, _) = self.same_unit(x)?;
+        Ok(Value(y.atan2(x), Unit::new(angle)))
+    }
+    /// The length of the hypotenuse of a right triangle with the two values as legs,
+    /// failing if they don't have the same dimension
+    pub fn hypot(self, rhs: Self) -> Result<Self, UnitError> {
+        let (a, b, unit) = self.same_unit(rhs)?;
+        Ok(Value(a.hypot(b), unit))
+    }
+    /// The smaller of the two values, failing if they don't have the same dimension
+    pub fn min(self, rhs: Self) -> Result<Self, UnitError> {
+        self.same_unit(rhs)?;
+        Ok(if rhs < self {rhs} else {self})
+    }
+    /// The larger of the two values, failing if they don't have the same dimension
+    pub fn max(self, rhs: Self) -> Result<Self, UnitError> {
+        self.same_unit(rhs)?;
+        Ok(if rhs > self {rhs} else {self})
+    }
+}
+
+#[test]
+fn maths_test() {
+    let si = UnitSystem::<f64>::si();
+    let val = |s| si.val_s(s).unwrap();
+
+    assert_eq!(val("9 km²").sqrt(), Ok(val("3 km")));
+    assert_eq!(val("8 m³/s³").cbrt(), Ok(val("2 m/s")));
+    assert_eq!(val("4 m³").sqrt(), Err(UnitError::FractionalRoot(VOLUME, 2)));
+    assert_eq!(val("4 m").root(0), Err(UnitError::ZeroRoot));
+    assert_eq!(val("4").root(i16::MIN), Err(UnitError::ExponentOverflow));
+    assert_eq!(val("4 Hz^(1/3)").root(i16::MAX), Err(UnitError::FractionalRoot(FREQUENCY * Exponent::new(1, 3), i16::MAX)));
+    assert_eq!(val("4 m²").root(-2), Ok(val("0.5 m⁻¹")));
+    assert_eq!(val("3 m").powi(2), Ok(val("9 m²")));
+    assert_eq!(val("1 m^20000").powi(2), Err(UnitError::ExponentOverflow));
+    assert_eq!(val("1 m^20000").checked_mul(val("1 m^20000")), Err(UnitError::ExponentOverflow));
+    assert_eq!(val("1 m^20000").checked_div(val("1 m^-20000")), Err(UnitError::ExponentOverflow));
+    assert_eq!(val("-5 °C").abs(), val("-5 °C").absolute());
+    assert_eq!(val("-2 s").abs(), val("2 s"));
+    assert!((val("2").ln().unwrap().0 - 2f64.ln()).abs() < 1e-12);
+    assert_eq!(val("1 m").exp(), Err(UnitError::DimensionMismatch(LENGTH, NUL)));
+    assert_eq!(val("1 s").sin(), Err(UnitError::DimensionMismatch(TIME, ANGLE)));
+    assert!((val("30 deg").sin().unwrap().0 - 0.5).abs() < 1e-12);
+    let tracked = UnitSystem::<f64>::si_with_angles();
+    assert!((tracked.val_s("0.25 turn").unwrap().cos().unwrap().0).abs() < 1e-12);
+    assert_eq!(val("3 m").hypot(val("400 cm")), Ok(val("5 m")));
+    assert_eq!(val("3 m").max(val("400 cm")), Ok(val("400 cm")));
+    assert_eq!(val("3 m").min(val("4 s")), Err(UnitError::DimensionMismatch(LENGTH, TIME)));
+    assert!((val("1 m").atan2(val("100 cm"), NUL).unwrap().0 - std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+    assert_eq!(val("0.5").asin(si.angle()).unwrap().1, Unit::new(NUL));
+
+    let angle = tracked.val_s("0.5").unwrap().asin(tracked.angle()).unwrap();
+    assert_eq!(angle.1.dimension, ANGLE);
+    assert!((tracked.as_(angle, "deg").0 - 30.).abs() < 1e-9);
+    let angle = tracked.val_s("-1 m").unwrap().atan2(tracked.val_s("0 m").unwrap(), tracked.angle()).unwrap();
+    assert!((tracked.as_(angle, "turn").0 + 0.25).abs() < 1e-12);
+}
@@ -0,0 +1,105 @@
+use num::Float;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+/// A metric prefix scaling a unit by a power of ten
+pub struct Prefix {
+    /// The symbol written in front of the unit, e.g. `"k"`
+    pub symbol: &'static str,
+    /// The name of the prefix, e.g. `"kilo"`
+    pub name: &'static str,
+    /// The power of ten this prefix multiplies by
+    pub exponent: i8,
+}
+
+impl Prefix {
+    /// The number a unit with this prefix is multiplied by
+    pub fn factor<N: Float>(&self) -> N {
+        let ten = N::from(10).unwrap();
+        // Dividing keeps negative powers exact for as long as the positive ones are
+        if self.exponent < 0 {
+            N::one() / ten.powi(-self.exponent as i32)
+        } else {
+            ten.powi(self.exponent as i32)
+        }
+    }
+}
+
+macro_rules! prefixes {
+    ($($symbol:expr, $name:expr, $exponent:expr;)*) => (
+        /// The SI prefixes from quecto to quetta, including those added in 2022
+        pub const SI_PREFIXES: &[Prefix] = &[$(
+            Prefix {
+                symbol: $symbol,
+                name: $name,
+                exponent: $exponent,
+            },
+        )*];
+    );
+}
+
+prefixes!{
+    "q", "quecto", -30;
+    "r", "ronto", -27;
+    "y", "yocto", -24;
+    "z", "zepto", -21;
+    "a", "atto", -18;
+    "f", "femto", -15;
+    "p", "pico", -12;
+    "n", "nano", -9;
+    "µ", "micro", -6;
+    "m", "milli", -3;
+    "c", "centi", -2;
+    "d", "deci", -1;
+    "da", "deca", 1;
+    "h", "hecto", 2;
+    "k", "kilo", 3;
+    "M", "mega", 6;
+    "G", "giga", 9;
+    "T", "tera", 12;
+    "P", "peta", 15;
+    "E", "exa", 18;
+    "Z", "zetta", 21;
+    "Y", "yotta", 24;
+    "R", "ronna", 27;
+    "Q", "quetta", 30;
+}
+
+/// Alternative symbols accepted for micro besides the micro sign `µ`
+const MICRO_ALIASES: &[&str] = &["u", "μ"];
+
+/// Returns the prefix with the given symbol
+///
+/// Besides the micro sign `µ`, the Greek letter `μ` and `u` are accepted for micro
+pub fn prefix_from_symbol(symbol: &str) -> Option<Prefix> {
+    let symbol = if MICRO_ALIASES.contains(&symbol) {"µ"} else {symbol};
+    SI_PREFIXES.iter().find(|p| p.symbol == symbol).cloned()
+}
+
+/// Returns every way `name` can be split into a prefix and a non-empty rest
+pub fn split_prefix(name: &str) -> impl Iterator<Item=(Prefix, &str)> {
+    SI_PREFIXES.iter()
+        .map(|p| p.symbol)
+        .chain(MICRO_ALIASES.iter().cloned())
+        .filter(move |s| name.len() > s.len() && name.starts_with(s))
+        .map(move |s| (prefix_from_symbol(s).unwrap(), &name[s.len()..]))
+}
+
+/// Returns every way `name` can be split into the name of a prefix and a non-empty rest,
+/// like `kilo` and `metres` for `kilometres`
+pub fn split_prefix_name(name: &str) -> impl Iterator<Item=(Prefix, &str)> {
+    SI_PREFIXES.iter()
+        .filter(move |p| name.len() > p.name.len() && name.starts_with(p.name))
+        .map(move |&p| (p, &name[p.name.len()..]))
+}
+
+#[test]
+fn prefix_test() {
+    assert_eq!(SI_PREFIXES.len(), 24);
+    assert_eq!(prefix_from_symbol("u"), prefix_from_symbol("µ"));
+    assert_eq!(prefix_from_symbol("k").unwrap().factor::<f64>(), 1e3);
+    assert_eq!(prefix_from_symbol("n").unwrap().factor::<f64>(), 1e-9);
+    assert_eq!(split_prefix("dam").map(|(p, r)| (p.name, r)).collect::<Vec<_>>(), vec![("deci", "am"), ("deca", "m")]);
+    assert_eq!(split_prefix("us").map(|(p, r)| (p.name, r)).collect::<Vec<_>>(), vec![("micro", "s")]);
+    assert_eq!(split_prefix("m").count(), 0);
+    assert_eq!(split_prefix_name("kilometres").map(|(p, r)| (p.symbol, r)).collect::<Vec<_>>(), vec![("k", "metres")]);
+}
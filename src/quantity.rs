@@ -0,0 +1,248 @@
+use super::*;
+
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::cmp::Ordering;
+
+use num::Float;
+use typenum::{Diff, Integer, Sum, N1, N2, N3, P1, P2, P3, P4, Z0};
+
+/// A dimension known at compile time
+///
+/// The parameters are the exponents of length, time, mass, current, temperature,
+/// amount of substance and luminous intensity as `typenum` integers,
+/// in the same order as the fields of `Dimension`.
+/// Only integer exponents can be represented this way, and angles aren't tracked.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Dims<L, T, M, I, Th, Nn, J>(PhantomData<(L, T, M, I, Th, Nn, J)>);
+
+/// Types describing a dimension at compile time
+pub trait Dim {
+    /// The runtime dimension described by this type
+    const DIMENSION: Dimension;
+}
+
+impl<L: Integer, T: Integer, M: Integer, I: Integer, Th: Integer, Nn: Integer, J: Integer> Dim for Dims<L, T, M, I, Th, Nn, J> {
+    const DIMENSION: Dimension = Dimension {
+        length: Exponent::integer(L::I16),
+        time: Exponent::integer(T::I16),
+        mass: Exponent::integer(M::I16),
+        current: Exponent::integer(I::I16),
+        temperature: Exponent::integer(Th::I16),
+        substance_amount: Exponent::integer(Nn::I16),
+        luminous_intensity: Exponent::integer(J::I16),
+        angle: Exponent::ZERO,
+    };
+}
+
+impl<L, T, M, I, Th, Nn, J, L2, T2, M2, I2, Th2, Nn2, J2> Add<Dims<L2, T2, M2, I2, Th2, Nn2, J2>> for Dims<L, T, M, I, Th, Nn, J>
+where L: Add<L2>, T: Add<T2>, M: Add<M2>, I: Add<I2>, Th: Add<Th2>, Nn: Add<Nn2>, J: Add<J2> {
+    type Output = Dims<Sum<L, L2>, Sum<T, T2>, Sum<M, M2>, Sum<I, I2>, Sum<Th, Th2>, Sum<Nn, Nn2>, Sum<J, J2>>;
+    fn add(self, _: Dims<L2, T2, M2, I2, Th2, Nn2, J2>) -> Self::Output {
+        Dims(PhantomData)
+    }
+}
+
+impl<L, T, M, I, Th, Nn, J, L2, T2, M2, I2, Th2, Nn2, J2> Sub<Dims<L2, T2, M2, I2, Th2, Nn2, J2>> for Dims<L, T, M, I, Th, Nn, J>
+where L: Sub<L2>, T: Sub<T2>, M: Sub<M2>, I: Sub<I2>, Th: Sub<Th2>, Nn: Sub<Nn2>, J: Sub<J2> {
+    type Output = Dims<Diff<L, L2>, Diff<T, T2>, Diff<M, M2>, Diff<I, I2>, Diff<Th, Th2>, Diff<Nn, Nn2>, Diff<J, J2>>;
+    fn sub(self, _: Dims<L2, T2, M2, I2, Th2, Nn2, J2>) -> Self::Output {
+        Dims(PhantomData)
+    }
+}
+
+/// A quantity whose dimension is checked at compile time
+///
+/// The number is stored in the coherent base units of `D`, like metres per second for velocities.
+/// Adding quantities of different dimensions doesn't compile,
+/// and multiplying or dividing them gives a quantity of the combined dimension.
+///
+/// Converting to a `Value` is free, while converting from one checks the dimension
+/// and converts the number to base units.
+///
+/// ```
+/// use pcm_calc::{Length, Time, Velocity};
+///
+/// let v: Velocity<f64> = Length::new(100.) / Time::new(8.);
+/// assert_eq!(v.value(), 12.5);
+/// ```
+///
+/// ```compile_fail
+/// use pcm_calc::{Length, Time};
+///
+/// let _ = Length::<f64>::new(1.) + Time::<f64>::new(1.);
+/// ```
+#[derive(Debug, Default)]
+pub struct Quantity<D, N: Float> {
+    value: N,
+    dimension: PhantomData<D>,
+}
+
+impl<D: Dim, N: Float> Quantity<D, N> {
+    /// Makes a quantity from a number in the coherent base units of the dimension
+    pub fn new(value: N) -> Self {
+        Quantity {
+            value,
+            dimension: PhantomData,
+        }
+    }
+    /// The number in the coherent base units of the dimension
+    pub fn value(self) -> N {
+        self.value
+    }
+    /// The number in the given unit
+    ///
+    /// Fails if the unit has another dimension
+    pub fn value_in(self, unit: &Unit<N>) -> Result<N, UnitError> {
+        if unit.dimension != D::DIMENSION {
+            return Err(UnitError::DimensionMismatch(D::DIMENSION, unit.dimension))
+        }
+        Ok(unit.from_base(self.value))
+    }
+    /// The dimension of the quantity
+    pub fn dimension() -> Dimension {
+        D::DIMENSION
+    }
+}
+
+impl<D: Dim, N: Float> From<Quantity<D, N>> for Value<N> {
+    fn from(q: Quantity<D, N>) -> Self {
+        Value(q.value, Unit::new(D::DIMENSION))
+    }
+}
+
+impl<D: Dim, N: Float> TryFrom<Value<N>> for Quantity<D, N> {
+    type Error = UnitError;
+    /// Converts the value to base units, failing if it has another dimension
+    fn try_from(Value(n, unit): Value<N>) -> Result<Self, UnitError> {
+        if unit.dimension != D::DIMENSION {
+            return Err(UnitError::DimensionMismatch(D::DIMENSION, unit.dimension))
+        }
+        Ok(Quantity::new(unit.to_base(n)))
+    }
+}
+
+impl<D: Dim, N: Float> Add for Quantity<D, N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Quantity::new(self.value + rhs.value)
+    }
+}
+
+impl<D: Dim, N: Float> Sub for Quantity<D, N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Quantity::new(self.value - rhs.value)
+    }
+}
+
+impl<D: Dim, N: Float> Neg for Quantity<D, N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Quantity::new(-self.value)
+    }
+}
+
+impl<D, D2, N: Float> Mul<Quantity<D2, N>> for Quantity<D, N>
+where D: Dim + Add<D2>, D2: Dim, D::Output: Dim {
+    type Output = Quantity<D::Output, N>;
+    fn mul(self, rhs: Quantity<D2, N>) -> Self::Output {
+        Quantity::new(self.value * rhs.value)
+    }
+}
+
+impl<D, D2, N: Float> Div<Quantity<D2, N>> for Quantity<D, N>
+where D: Dim + Sub<D2>, D2: Dim, D::Output: Dim {
+    type Output = Quantity<D::Output, N>;
+    fn div(self, rhs: Quantity<D2, N>) -> Self::Output {
+        Quantity::new(self.value / rhs.value)
+    }
+}
+
+impl<D: Dim, N: Float> Mul<N> for Quantity<D, N> {
+    type Output = Self;
+    fn mul(self, rhs: N) -> Self {
+        Quantity::new(self.value * rhs)
+    }
+}
+
+impl<D: Dim, N: Float> Div<N> for Quantity<D, N> {
+    type Output = Self;
+    fn div(self, rhs: N) -> Self {
+        Quantity::new(self.value / rhs)
+    }
+}
+
+// Implemented by hand, since deriving would require `D` to implement them as well
+impl<D, N: Float> Clone for Quantity<D, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D, N: Float> Copy for Quantity<D, N> {}
+
+impl<D, N: Float> PartialEq for Quantity<D, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<D: Dim, N: Float> PartialOrd for Quantity<D, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+macro_rules! quantities {
+    ($($name:ident, $doc:expr; $l:ty, $t:ty, $m:ty, $i:ty, $th:ty, $n:ty, $j:ty;)*) => ($(
+        #[doc = $doc]
+        pub type $name<N> = Quantity<Dims<$l, $t, $m, $i, $th, $n, $j>, N>;
+    )*);
+}
+
+quantities!{
+    Dimensionless, "A quantity without a dimension"; Z0, Z0, Z0, Z0, Z0, Z0, Z0;
+    Length, "A length in metres"; P1, Z0, Z0, Z0, Z0, Z0, Z0;
+    Time, "A time in seconds"; Z0, P1, Z0, Z0, Z0, Z0, Z0;
+    Mass, "A mass in kilogrammes"; Z0, Z0, P1, Z0, Z0, Z0, Z0;
+    Current, "A current in amperes"; Z0, Z0, Z0, P1, Z0, Z0, Z0;
+    Temperature, "A temperature in kelvin"; Z0, Z0, Z0, Z0, P1, Z0, Z0;
+    AmountOfSubstance, "An amount of substance in moles"; Z0, Z0, Z0, Z0, Z0, P1, Z0;
+    LuminousIntensity, "A luminous intensity in candelas"; Z0, Z0, Z0, Z0, Z0, Z0, P1;
+
+    Area, "An area in square metres"; P2, Z0, Z0, Z0, Z0, Z0, Z0;
+    Volume, "A volume in cubic metres"; P3, Z0, Z0, Z0, Z0, Z0, Z0;
+    Frequency, "A frequency in hertz"; Z0, N1, Z0, Z0, Z0, Z0, Z0;
+    Velocity, "A velocity in metres per second"; P1, N1, Z0, Z0, Z0, Z0, Z0;
+    Acceleration, "An acceleration in metres per second squared"; P1, N2, Z0, Z0, Z0, Z0, Z0;
+    Force, "A force in newtons"; P1, N2, P1, Z0, Z0, Z0, Z0;
+    Energy, "An energy in joules"; P2, N2, P1, Z0, Z0, Z0, Z0;
+    Power, "A power in watts"; P2, N3, P1, Z0, Z0, Z0, Z0;
+    Pressure, "A pressure in pascals"; N1, N2, P1, Z0, Z0, Z0, Z0;
+    Charge, "A charge in coulombs"; Z0, P1, Z0, P1, Z0, Z0, Z0;
+    Voltage, "A voltage in volts"; P2, N3, P1, N1, Z0, Z0, Z0;
+    Resistance, "A resistance in ohms"; P2, N3, P1, N2, Z0, Z0, Z0;
+    Capacitance, "A capacitance in farads"; N2, P4, N1, P2, Z0, Z0, Z0;
+}
+
+#[test]
+fn quantity_test() {
+    let si = UnitSystem::<f64>::si();
+    let distance = Length::try_from(si.val(3., "km").unwrap()).unwrap();
+    let time = Time::new(100.);
+    let velocity: Velocity<f64> = distance / time;
+    assert_eq!(velocity.value(), 30.);
+    assert_eq!(velocity.value_in(&si.unit_from_str("km/h").unwrap()).map(f64::round), Ok(108.));
+
+    let force: Force<f64> = Mass::new(2.) * (velocity / time);
+    assert_eq!(Value::from(force), si.val(0.6, "N").unwrap());
+    let energy: Energy<f64> = force * distance;
+    assert_eq!(Quantity::try_from(si.val(1.8, "kJ").unwrap()), Ok(energy));
+    assert_eq!(Voltage::<f64>::dimension(), VOLTAGE);
+    assert_eq!(Capacitance::<f64>::dimension(), CAPACITANCE);
+
+    assert!(Time::<f64>::try_from(si.val(1., "m").unwrap()).is_err());
+    assert_eq!(Temperature::try_from(si.val(25., "°C").unwrap()).map(|t| t.value()), Ok(298.15));
+}
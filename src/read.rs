@@ -2,75 +2,533 @@ use super::*;
 
 use num::Float;
 
-#[derive(Debug)]
-enum BuildState {
-    Unit,
-    Exponent
+/// Whether `c` can be part of the name of a unit, like the letters, `°`, `_` and subscript digits
+pub fn is_unit_char(c: char) -> bool {
+    c.is_alphabetic() || c == '°' || c == '_' || ('₀'..='₉').contains(&c)
 }
 
-pub fn unit_from_str<N: Float>(sys: &UnitSystem<N>, s: &str) -> Option<Unit<N>> {
-    let mut proto_unit = String::with_capacity(4);
-    let mut proto_exponent = String::with_capacity(4);
-    let mut cur_state = BuildState::Unit;
-    let mut inverse = false;
+fn is_superscript(c: char) -> bool {
+    c == '⁻' || c == 'ᐟ' || super_to_num(c).is_ascii_digit() && !c.is_ascii_digit()
+}
 
-    let mut unit = Unit::new(NUL);
+/// Recursive descent parser for composite units
+///
+/// The grammar is:
+///
+/// ```text
+/// expr     = [product] {("*" | "·" | "/" | "per") product}
+/// product  = power {power}
+/// power    = ["square" | "cubic"] primary [exponent | "squared" | "cubed"]
+/// primary  = number | symbol | "(" expr ")"
+/// exponent = superscript | "^" number | "^" "(" rational ")"
+/// ```
+///
+/// Symbols can be units, their aliases or long names, so `square metres per second`
+/// is read like `m²/s`. The words for powers are only read as such if they aren't units.
+/// Names of several words joined by spaces or hyphens, like `degrees Celsius`, `kilowatt-hours`
+/// or `pounds per square inch`, are read as one symbol, taking the longest one that is a unit.
+///
+/// Exponents may be rational, like `Hz^(1/2)`, `Hz^-0.5` or `Hz⁻¹ᐟ²`.
+///
+/// Multiplication by juxtaposition binds tighter than `*`, `·`, `/` and `per`,
+/// which all have the same precedence and associate to the left.
+/// So `J/kg K` is J/(kg·K) while `J/kg·K` is (J/kg)·K and `m/s/s` is m/s².
+/// A symbol directly followed by an integer (like `m2` or `s-1`) is raised to that power.
+struct Parser<'a, N: Float + 'a> {
+    sys: &'a UnitSystem<N>,
+    src: &'a str,
+    pos: usize,
+}
 
-    for c in s.replace('/', " /").chars().filter(|&c| c != '^').map(super_to_num).chain(Some(' ')) {
-        let c = match c {
-            '*' | '·' => ' ',
-            c => c,
-        };
-        if proto_unit.is_empty() && c == ' ' {
-            continue
+impl<'a, N: Float> Parser<'a, N> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.bump();
         }
-        match cur_state {
-            _ if c == '/' => inverse = true,
-            BuildState::Unit if c == ' ' => {
-                let un = sys.get_unit(&proto_unit)? * if inverse{-1}else{1};
-                proto_unit.clear();
-                unit = unit + un;
-                inverse = false;
+        &self.src[start..self.pos]
+    }
+    /// The error for the character at the current position
+    fn unexpected(&self) -> ParseError {
+        match self.peek() {
+            Some(c) => ParseError::Unexpected {
+                text: c.to_string(),
+                span: self.pos..self.pos+c.len_utf8(),
+            },
+            None => ParseError::UnexpectedEnd {
+                span: self.pos..self.pos,
+            },
+        }
+    }
+    /// The error for the unit from `start` to the current position, whose dimension overflowed
+    fn overflow(&self, start: usize) -> ParseError {
+        let text = self.src[start..self.pos].trim_end();
+        ParseError::ExponentOverflow {
+            text: text.to_owned(),
+            span: start..start+text.len(),
+        }
+    }
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+    /// Returns true if the next word is `word`
+    fn at_word(&self, word: &str) -> bool {
+        let rest = self.rest();
+        rest.starts_with(word) && !rest[word.len()..].chars().next().is_some_and(is_unit_char)
+    }
+    /// Returns true if the next word is `per`
+    fn at_per(&self) -> bool {
+        self.at_word("per")
+    }
+    /// Reads the longest name of several words joined by single spaces or hyphens that is a unit
+    fn multi_word_name(&mut self) -> Option<&'a str> {
+        let rest = self.rest();
+        let mut ends = Vec::new();
+        let mut chars = rest.char_indices().peekable();
+        loop {
+            match chars.next() {
+                Some((_, c)) if is_unit_char(c) => (),
+                Some((i, c)) if (c == ' ' || c == '-') && chars.peek().is_some_and(|&(_, c)| is_unit_char(c)) => ends.push(i),
+                Some((i, _)) => break ends.push(i),
+                None => break ends.push(rest.len()),
             }
-            BuildState::Unit => {
-                if c.is_alphabetic() {
-                    proto_unit.push(c);
-                } else if c == '-' || c.is_numeric() {
-                    cur_state = BuildState::Exponent;
-                    proto_exponent.push(c);
-                } else {
-                    return None;
+        }
+        // The first end is that of a single word
+        let end = ends.into_iter().skip(1).rev().find(|&end| self.sys.get_unit(&rest[..end]).is_some())?;
+        self.pos += end;
+        Some(&rest[..end])
+    }
+    /// Reads the next word if it is one of `words` and not a unit, returning its power
+    fn power_word(&mut self, words: &[(&str, i16)]) -> Option<i16> {
+        let &(word, power) = words.iter().find(|&&(word, _)| self.at_word(word))?;
+        if self.sys.get_unit(word).is_some() {
+            return None
+        }
+        self.pos += word.len();
+        Some(power)
+    }
+
+    fn expr(&mut self) -> Result<Unit<N>, ParseError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut unit = if self.peek() == Some('/') || self.at_per() {
+            Unit::new(NUL)
+        } else {
+            self.product()?
+        };
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('*') | Some('·') => {
+                    self.bump();
+                    let rhs = self.product()?;
+                    unit = unit.checked_add(rhs).ok_or_else(|| self.overflow(start))?;
+                }
+                Some('/') => {
+                    self.bump();
+                    let rhs = self.product()?;
+                    unit = unit.checked_sub(rhs).ok_or_else(|| self.overflow(start))?;
+                }
+                _ if self.at_per() => {
+                    self.pos += "per".len();
+                    let rhs = self.product()?;
+                    unit = unit.checked_sub(rhs).ok_or_else(|| self.overflow(start))?;
+                }
+                _ => return Ok(unit),
+            }
+        }
+    }
+    fn product(&mut self) -> Result<Unit<N>, ParseError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut unit = self.power()?;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(c) if (c == '(' || c.is_ascii_digit() || is_unit_char(c)) && !self.at_per() => {
+                    let rhs = self.power()?;
+                    unit = unit.checked_add(rhs).ok_or_else(|| self.overflow(start))?;
                 }
+                _ => return Ok(unit),
             }
-            BuildState::Exponent => {
-                if c == '-' || c.is_numeric() {
-                    proto_exponent.push(c);
-                } else if c.is_alphabetic() || c == ' ' {
-                    let ex = proto_exponent.parse().ok()?;
-                    let un = sys.get_unit(&proto_unit)? * ex * if inverse{-1}else{1};
-                    unit = unit + un;
-                    inverse = false;
-                    proto_exponent.clear();
-                    proto_unit.clear();
-
-                    if c != ' ' {
-                        proto_unit.push(c);
-                    }
-                    cur_state = BuildState::Unit;
+        }
+    }
+    fn power(&mut self) -> Result<Unit<N>, ParseError> {
+        let start = self.pos;
+        if let Some(power) = self.power_word(&[("square", 2), ("cubic", 3)]) {
+            self.skip_whitespace();
+            let unit = self.primary()?.0;
+            return unit.checked_mul(Exponent::integer(power)).ok_or_else(|| self.overflow(start))
+        }
+        let (unit, symbol) = self.primary()?;
+        let before_space = self.pos;
+        self.skip_whitespace();
+        if let Some(power) = self.power_word(&[("squared", 2), ("cubed", 3)]) {
+            return unit.checked_mul(Exponent::integer(power)).ok_or_else(|| self.overflow(start))
+        }
+        self.pos = before_space;
+        let exponent = match self.peek() {
+            Some('^') => {
+                self.bump();
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    self.bump();
+                    let exponent = self.exponent(|c| c.is_ascii_digit() || c.is_whitespace() || "-./".contains(c))?;
+                    self.expect(')')?;
+                    exponent
                 } else {
-                    return None;
+                    self.exponent(|c| c.is_ascii_digit() || c == '-' || c == '.')?
                 }
             }
+            Some(c) if is_superscript(c) => self.exponent(is_superscript)?,
+            Some(c) if symbol && (c == '-' || c.is_ascii_digit()) => Exponent::integer(self.integer()?),
+            _ => return Ok(unit),
+        };
+        unit.checked_mul(exponent).ok_or_else(|| self.overflow(start))
+    }
+    /// Reads a possibly rational exponent made of the characters accepted by `f`
+    fn exponent<F: Fn(char) -> bool>(&mut self, f: F) -> Result<Exponent, ParseError> {
+        let start = self.pos;
+        let text = self.take_while(f);
+        if text.trim().is_empty() {
+            return Err(self.unexpected())
+        }
+        let exponent: String = text.chars().map(super_to_num).collect();
+        exponent.trim().parse().map_err(|_| ParseError::InvalidExponent {
+            text: text.to_owned(),
+            span: start..self.pos,
+        })
+    }
+    fn integer(&mut self) -> Result<i16, ParseError> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.bump();
+        }
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            return Err(self.unexpected())
         }
+        let int = &self.src[start..self.pos];
+        int.parse().map_err(|_| ParseError::InvalidExponent {
+            text: int.to_owned(),
+            span: start..self.pos,
+        })
     }
-    Some(unit)
+    /// Returns the unit and whether it was a bare symbol
+    fn primary(&mut self) -> Result<(Unit<N>, bool), ParseError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                self.bump();
+                let unit = self.expr()?;
+                self.skip_whitespace();
+                self.expect(')')?;
+                Ok((unit, false))
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.number();
+                let n = number.parse::<f64>().ok().and_then(N::from).ok_or_else(|| ParseError::InvalidNumber {
+                    text: number.to_owned(),
+                    span: start..self.pos,
+                })?;
+                Ok((Unit::with_factor(NUL, n), false))
+            }
+            Some(c) if is_unit_char(c) => {
+                let name = match self.multi_word_name() {
+                    Some(name) => name,
+                    None => self.take_while(is_unit_char),
+                };
+                let unit = self.sys.get_unit(name).ok_or_else(|| ParseError::UnknownUnit {
+                    name: name.to_owned(),
+                    span: start..self.pos,
+                    suggestions: self.sys.suggestions(name),
+                })?;
+                Ok((unit, true))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+    fn number(&mut self) -> &'a str {
+        let start = self.pos;
+        self.take_while(|c| c.is_ascii_digit() || c == '.');
+        let mut exp = self.rest().chars();
+        if let Some('e') | Some('E') = exp.next() {
+            let mut len = 1;
+            let mut next = exp.next();
+            if let Some('-') | Some('+') = next {
+                len += 1;
+                next = exp.next();
+            }
+            if next.is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += len;
+                self.take_while(|c| c.is_ascii_digit());
+            }
+        }
+        &self.src[start..self.pos]
+    }
+}
+
+/// Parses the unit in `s` starting at byte `start`
+fn parse_unit<N: Float>(sys: &UnitSystem<N>, s: &str, start: usize) -> Result<Unit<N>, ParseError> {
+    let mut parser = Parser {
+        sys,
+        src: s,
+        pos: start,
+    };
+    parser.skip_whitespace();
+    if parser.peek().is_none() {
+        return Ok(Unit::new(NUL))
+    }
+    let unit = parser.expr()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected())
+    }
+    Ok(unit)
+}
+
+pub fn unit_from_str<N: Float>(sys: &UnitSystem<N>, s: &str) -> Result<Unit<N>, ParseError> {
+    parse_unit(sys, s, 0)
 }
 
 use std::str::FromStr;
+use std::ops::Range;
 
-pub fn value_from_str<N: Float + FromStr>(sys: &UnitSystem<N>, s: &str) -> Option<Value<N>> {
-    let index = s.find(<char>::is_whitespace).unwrap_or(s.len());
-    let (val, unit) = s.split_at(index);
+/// Returns true if the last word is `per`
+fn ends_with_per(s: &str) -> bool {
+    let s = s.trim_end();
+    s.ends_with("per") && !s[..s.len()-"per".len()].ends_with(is_unit_char)
+}
+
+/// Returns the byte index of each part of a compound value like `5 ft 3 in`
+///
+/// A part starts with a number after whitespace following the unit of the part before it,
+/// unless it's inside parentheses or after an operator (as in `1 m/ 2 s`)
+fn compound_parts(s: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut depth = 0;
+    let mut after_space = false;
+    let mut has_unit = false;
+    let mut last = None;
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() {
+            after_space = true;
+            continue
+        }
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => (),
+        }
+        let after_operator = last.is_some_and(|l| "/*·^(".contains(l)) || ends_with_per(&s[..i]);
+        if depth == 0 && after_space && has_unit && !after_operator && (c.is_ascii_digit() || c == '.') {
+            starts.push(i);
+            has_unit = false;
+        } else if is_unit_char(c) || c == ')' {
+            has_unit = true;
+        }
+        after_space = false;
+        last = Some(c);
+    }
+    starts
+}
+
+/// Parses a number, reporting `span` if it's invalid
+fn parse_number<N: FromStr>(text: &str, span: Range<usize>) -> Result<N, ParseError> {
+    text.parse().map_err(|_| ParseError::InvalidNumber {
+        text: text.to_owned(),
+        span,
+    })
+}
+
+/// Parses a value with a single unit from `s` starting at byte `start`
+///
+/// The number may have a standard uncertainty, either as in `9.81 ± 0.02` (or `+-`)
+/// or as in `9.81(2)`, where the digits in parentheses are the uncertainty in the last digits
+fn parse_value<N: Float + FromStr>(sys: &UnitSystem<N>, s: &str, start: usize) -> Result<Uncertain<N>, ParseError> {
+    let start = start + s[start..].len() - s[start..].trim_start().len();
+    let end = s[start..].find(|c: char| c.is_whitespace() || c == '(' || c == '±').map_or(s.len(), |i| start+i);
+    let number = &s[start..end];
+    let mut val = parse_number(number, start..end)?;
+
+    let mut pos = end;
+    let mut uncertainty = None;
+    if s[pos..].starts_with('(') {
+        let close = s[pos..].find(')').map(|i| pos+i).ok_or(ParseError::UnexpectedEnd {span: s.len()..s.len()})?;
+        let digits = &s[pos+1..close];
+        // The exponent of the number, which may come after the parentheses as in `1.23(4)e5`
+        let exp_len = s[close+1..].find(<char>::is_whitespace).unwrap_or(s.len()-close-1);
+        let exponent = &s[close+1..close+1+exp_len];
+        let exp_len = if exponent.starts_with(['e', 'E']) {exp_len} else {0};
+        let exponent = if exp_len > 0 {
+            val = parse_number(&format!("{}{}", number, exponent), start..close+1+exp_len)?;
+            exponent[1..].parse().map_err(|_| ParseError::InvalidNumber {
+                text: s[start..close+1+exp_len].to_owned(),
+                span: start..close+1+exp_len,
+            })?
+        } else {
+            0
+        };
+        uncertainty = Some(if digits.contains('.') {
+            parse_number::<N>(digits, pos+1..close)? * N::from(10).unwrap().powi(exponent)
+        } else {
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseError::InvalidNumber {
+                    text: digits.to_owned(),
+                    span: pos+1..close,
+                })
+            }
+            let decimals = number.find('.').map_or(0, |point| number.len() - point - 1);
+            parse_number(&format!("{}e{}", digits, exponent - decimals as i32), pos+1..close)?
+        });
+        pos = close + 1 + exp_len;
+    } else {
+        let rest = s[pos..].trim_start();
+        let sign = if rest.starts_with('±') {"±"} else if rest.starts_with("+-") {"+-"} else {""};
+        if !sign.is_empty() {
+            let after = s.len() - rest.len() + sign.len();
+            let u_start = after + s[after..].len() - s[after..].trim_start().len();
+            let u_end = s[u_start..].find(<char>::is_whitespace).map_or(s.len(), |i| u_start+i);
+            uncertainty = Some(parse_number(&s[u_start..u_end], u_start..u_end)?);
+            pos = u_end;
+        }
+    }
+
+    let value = Value(val, parse_unit(sys, s, pos)?);
+    Ok(match uncertainty {
+        Some(uncertainty) => Uncertain::new(value, uncertainty),
+        None => Uncertain::exact(value),
+    })
+}
+
+/// Parses a value with an uncertainty, which may be compound like `5 ft 3 in` or `1 h 23 min 4 s`
+///
+/// The parts of a compound value are added up in the unit of the last part.
+/// A leading minus sign applies to the whole value, so `-1 min 30 s` is -90 s
+pub fn uncertain_from_str<N: Float + FromStr>(sys: &UnitSystem<N>, s: &str) -> Result<Uncertain<N>, ParseError> {
+    let starts = compound_parts(s);
+    let ends = starts.iter().skip(1).cloned().chain(Some(s.len()));
+    let negative = s.trim_start().starts_with('-');
+
+    let mut sum: Option<Uncertain<N>> = None;
+    for (start, end) in starts.iter().cloned().zip(ends) {
+        let mut part = parse_value(sys, &s[..end], start)?;
+        if negative && sum.is_some() {
+            part = part * -N::one();
+        }
+        sum = Some(match sum {
+            None => part,
+            Some(sum) => part.checked_add(&sum).map_err(|_| ParseError::InvalidCompound {
+                text: s[start..end].trim_end().to_owned(),
+                span: start..start+s[start..end].trim_end().len(),
+            })?,
+        });
+    }
+    Ok(sum.unwrap())
+}
+
+/// Parses a value like `uncertain_from_str`, leaving out its uncertainty
+pub fn value_from_str<N: Float + FromStr>(sys: &UnitSystem<N>, s: &str) -> Result<Value<N>, ParseError> {
+    uncertain_from_str(sys, s).map(|u| u.value)
+}
+
+#[test]
+fn grammar_test() {
+    let si = UnitSystem::<f64>::si();
+    let unit = |s| unit_from_str(&si, s);
+
+    assert_eq!(unit("kg·m/(s²·A)"), Ok(Unit::new(FORCE - CURRENT)));
+    assert_eq!(unit("kg m² s^(-3) A^-1"), Ok(Unit::new(VOLTAGE)));
+    assert_eq!(unit("m/s/s"), Ok(Unit::new(ACCELERATION)));
+    assert_eq!(unit("J/kg K"), unit("J/(kg·K)"));
+    assert_eq!(unit("J/kg·K"), Ok(Unit::new(ENERGY - MASS + TEMPERATURE)));
+    assert_eq!(unit("m per s"), Ok(Unit::new(VELOCITY)));
+    assert_eq!(unit("/s"), Ok(Unit::new(FREQUENCY)));
+    assert_eq!(unit("s-1"), Ok(Unit::new(FREQUENCY)));
+    assert_eq!(unit("1/(100 km)"), Ok(Unit::with_factor(NUL - LENGTH, 1e-5)));
+    assert_eq!(unit("°C").map(|u| u.is_affine()), Ok(true));
+
+    let noise_density = Unit::new(VOLTAGE - FREQUENCY * Exponent::new(1, 2));
+    assert_eq!(unit("V/Hz^(1/2)"), Ok(noise_density));
+    assert_eq!(unit("V Hz^-0.5"), Ok(noise_density));
+    assert_eq!(unit("V·Hz⁻¹ᐟ²"), Ok(noise_density));
+    assert_eq!(unit("km^( 1 / 2 )").map(|u| u.factor), Ok(1000f64.sqrt()));
+}
+
+#[test]
+fn parse_error_test() {
+    let si = UnitSystem::<f64>::si();
+
+    assert_eq!(unit_from_str(&si, "m/(s"), Err(ParseError::UnexpectedEnd{span: 4..4}));
+    assert_eq!(unit_from_str(&si, "m s^x"), Err(ParseError::Unexpected{text: "x".to_owned(), span: 4..5}));
+    assert_eq!(unit_from_str(&si, "m^99999"), Err(ParseError::InvalidExponent{text: "99999".to_owned(), span: 2..7}));
+    assert_eq!(value_from_str(&si, "3,5 m"), Err(ParseError::InvalidNumber{text: "3,5".to_owned(), span: 0..3}));
+    assert_eq!(unit_from_str(&si, "m^20000 m^20000"), Err(ParseError::ExponentOverflow{text: "m^20000 m^20000".to_owned(), span: 0..15}));
+    assert_eq!(unit_from_str(&si, "s (m^20000)/m^-20000"), Err(ParseError::ExponentOverflow{text: "s (m^20000)/m^-20000".to_owned(), span: 0..20}));
+    assert_eq!(unit_from_str(&si, "(m^20000)^2 s"), Err(ParseError::ExponentOverflow{text: "(m^20000)^2".to_owned(), span: 0..11}));
+    assert_eq!(unit_from_str(&si, "cubic (m^20000)"), Err(ParseError::ExponentOverflow{text: "cubic (m^20000)".to_owned(), span: 0..15}));
+
+    let err = value_from_str(&si, "90 kmh").unwrap_err();
+    assert_eq!(err.span(), 3..6);
+    assert_eq!(err.text(), "kmh");
+    assert_eq!(err, ParseError::UnknownUnit{name: "kmh".to_owned(), span: 3..6, suggestions: vec!["km".to_owned(), "kWh".to_owned()]});
+    assert_eq!(si.suggestions("kwh"), vec!["kWh".to_owned()]);
+}
+
+#[test]
+fn compound_test() {
+    let us = UnitSystem::<f64>::us_customary();
+    let same = |a: &str, b: f64, unit: &str| {
+        let a = value_from_str(&us, a).unwrap();
+        let b = us.val(b, unit).unwrap();
+        assert_eq!(a.1.dimension, b.1.dimension);
+        assert!((a.1.to_base(a.0) - b.1.to_base(b.0)).abs() < 1e-9, "{:?} != {:?}", a, b);
+    };
+
+    same("5 ft 3 in", 63., "in");
+    same("1 h 23 min 4 s", 4984., "s");
+    same("2 lb 4 oz", 36., "oz");
+    same("3 km / 2 h", 1.5, "km/h");
+    same("3 per 2 s", 1.5, "Hz");
+    same("5 1/(100 km)", 5., "1/(100 km)");
+    same("-1 h 23 min 4 s", -4984., "s");
+    let minus_90 = format!("{}", us.display(&us.val(-90., "s").unwrap()).compound(&["min", "s"]).unwrap());
+    assert_eq!(minus_90, "-1 min 30 s");
+    same(&minus_90, -90., "s");
+    assert_eq!(value_from_str(&us, "5 ft 3 lb"), Err(ParseError::InvalidCompound{text: "3 lb".to_owned(), span: 5..9}));
+}
 
-    Some(Value(val.parse().ok()?, unit_from_str(sys, unit)?))
+#[test]
+fn uncertain_parse_test() {
+    let si = UnitSystem::<f64>::si();
+    let g = uncertain_from_str(&si, "9.81 ± 0.02 m/s²").unwrap();
+    assert_eq!(g.value, si.val(9.81, "m/s²").unwrap());
+    assert_eq!(g.uncertainty(), 0.02);
+    assert_eq!(uncertain_from_str(&si, "9.81(2) m/s²").unwrap().uncertainty(), 0.02);
+    assert_eq!(uncertain_from_str(&si, "9.81 +- 0.02 m/s²").unwrap().uncertainty(), 0.02);
+    assert_eq!(uncertain_from_str(&si, "1.2345(67)e3 m").unwrap().uncertainty(), 6.7);
+    assert_eq!(uncertain_from_str(&si, "12.3(1.2) m").unwrap().uncertainty(), 1.2);
+    assert_eq!(uncertain_from_str(&si, "1 m").unwrap().uncertainty(), 0.);
+    assert_eq!(value_from_str(&si, "9.81(2) m/s²"), si.val(9.81, "m/s²"));
+    assert_eq!(uncertain_from_str(&si, "9.81(x) m").map(|_| ()), Err(ParseError::InvalidNumber{text: "x".to_owned(), span: 5..6}));
+    assert_eq!(uncertain_from_str(&si, "9.81 ± m").map(|_| ()), Err(ParseError::InvalidNumber{text: "m".to_owned(), span: 8..9}));
 }
@@ -0,0 +1,148 @@
+use super::*;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use num::Float;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde::Deserialize;
+
+/// Integer exponents are written as numbers and others as strings like `"-1/2"`
+impl Serialize for Exponent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_integer() {
+            serializer.serialize_i16(self.numer())
+        } else {
+            serializer.collect_str(self)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Exponent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ExponentVisitor;
+
+        impl<'de> Visitor<'de> for ExponentVisitor {
+            type Value = Exponent;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an integer or a fraction like \"1/2\"")
+            }
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<Exponent, E> {
+                if n < i16::MIN as i64 || n > i16::MAX as i64 {
+                    return Err(E::invalid_value(de::Unexpected::Signed(n), &self))
+                }
+                Ok(Exponent::integer(n as i16))
+            }
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<Exponent, E> {
+                if n > i16::MAX as u64 {
+                    return Err(E::invalid_value(de::Unexpected::Unsigned(n), &self))
+                }
+                Ok(Exponent::integer(n as i16))
+            }
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Exponent, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ExponentVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Value")]
+struct ValueRepr<N: Float> {
+    magnitude: N,
+    unit: Unit<N>,
+}
+
+/// Values are written as a struct with the magnitude and the unit
+impl<N: Float + Serialize> Serialize for Value<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serializer.serialize_struct("Value", 2)?;
+        value.serialize_field("magnitude", &self.0)?;
+        value.serialize_field("unit", &self.1)?;
+        value.end()
+    }
+}
+
+impl<'de, N: Float + Deserialize<'de>> Deserialize<'de> for Value<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ValueRepr::deserialize(deserializer).map(|v| Value(v.magnitude, v.unit))
+    }
+}
+
+/// Deserialises a `Value` from either a string like `"3.2 km/h"` read with a `UnitSystem`,
+/// or the structured form `Value` is serialised as
+///
+/// Errors from reading the string are reported as deserialisation errors
+pub struct ValueSeed<'a, N: Float + 'a> {
+    sys: &'a UnitSystem<N>,
+}
+
+impl<'a, N: Float + 'a> ValueSeed<'a, N> {
+    /// Creates a seed reading strings with the given system
+    pub fn new(sys: &'a UnitSystem<N>) -> Self {
+        ValueSeed {sys}
+    }
+}
+
+impl<'a, 'de, N: Float + FromStr + Deserialize<'de> + 'a> DeserializeSeed<'de> for ValueSeed<'a, N> {
+    type Value = Value<N>;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value<N>, D::Error> {
+        struct ValueVisitor<'a, 'de, N: Float + 'a> {
+            sys: &'a UnitSystem<N>,
+            lifetime: PhantomData<&'de ()>,
+        }
+
+        impl<'a, 'de, N: Float + FromStr + Deserialize<'de> + 'a> Visitor<'de> for ValueVisitor<'a, 'de, N> {
+            type Value = Value<N>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a value like \"3.2 km/h\" or a struct with a magnitude and a unit")
+            }
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Value<N>, E> {
+                value_from_str(self.sys, s).map_err(E::custom)
+            }
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Value<N>, A::Error> {
+                Value::deserialize(MapAccessDeserializer::new(map))
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Value<N>, A::Error> {
+                Value::deserialize(SeqAccessDeserializer::new(seq))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor {sys: self.sys, lifetime: PhantomData})
+    }
+}
+
+/// Displays are written as strings, which `ValueSeed` can read back
+impl<'a, N: Float + fmt::Display + 'a> Serialize for UnitDisplay<'a, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[test]
+fn serde_test() {
+    let si = UnitSystem::<f64>::si();
+    let speed = si.val(3.2, "km/h").unwrap();
+
+    let json = serde_json::to_string(&speed).unwrap();
+    assert_eq!(serde_json::from_str::<Value<f64>>(&json).unwrap(), speed);
+    let noise = si.val(4., "nV/Hz^(1/2)").unwrap();
+    let json = serde_json::to_string(&noise).unwrap();
+    assert!(json.contains(r#""time":"-5/2""#));
+    assert_eq!(serde_json::from_str::<Value<f64>>(&json).unwrap(), noise);
+    assert_eq!(serde_json::from_str::<Dimension>(r#"{"length": 1, "time": -1}"#).unwrap(), VELOCITY);
+
+    let read = |json: &str| ValueSeed::new(&si).deserialize(&mut serde_json::Deserializer::from_str(json));
+    assert_eq!(read(r#""3.2 km/h""#).unwrap(), speed);
+    assert_eq!(read(&serde_json::to_string(&speed).unwrap()).unwrap(), speed);
+    assert!(read(r#""3.2 kmh""#).unwrap_err().to_string().contains("kmh"));
+
+    let display = serde_json::to_string(&si.display(&si.val(2., "mW").unwrap()).auto_prefix()).unwrap();
+    assert_eq!(display, r#""2 mW""#);
+    assert_eq!(read(&display).unwrap(), si.val(2., "mW").unwrap());
+}
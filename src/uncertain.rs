@@ -0,0 +1,234 @@
+use super::*;
+
+use std::collections::BTreeMap;
+use std::ops::{Add, Div, Mul, Sub};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use num::Float;
+
+/// Counter giving every measurement its own source of uncertainty
+static NEXT_SOURCE: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, PartialEq)]
+/// A value with a standard uncertainty
+///
+/// Uncertainties are propagated to first order. Every measurement made with `Uncertain::new`
+/// is an independent source of uncertainty, and results keep track of how much each source
+/// contributes to them, so using the same measurement twice (like in `x - x`) is correlated.
+pub struct Uncertain<N: Float> {
+    /// The measured value
+    pub value: Value<N>,
+    /// How much each source contributes to the uncertainty, in the unit of the value
+    sources: BTreeMap<usize, N>,
+}
+
+/// Estimates the derivative of `f` at `x` with a central difference
+fn derivative<N: Float, F: Fn(N) -> N>(f: F, x: N) -> N {
+    let h = N::epsilon().cbrt() * x.abs().max(N::one());
+    (f(x + h) - f(x - h)) / (h + h)
+}
+
+impl<N: Float> Uncertain<N> {
+    /// Creates a measurement with the given standard uncertainty in the unit of the value
+    ///
+    /// Its uncertainty is independent of that of every other measurement
+    pub fn new(value: Value<N>, uncertainty: N) -> Self {
+        let mut sources = BTreeMap::new();
+        if uncertainty != N::zero() {
+            sources.insert(NEXT_SOURCE.fetch_add(1, Ordering::Relaxed), uncertainty.abs());
+        }
+        Uncertain {value, sources}
+    }
+    /// Creates a value without any uncertainty
+    pub fn exact(value: Value<N>) -> Self {
+        Uncertain {value, sources: BTreeMap::new()}
+    }
+    /// The standard uncertainty in the unit of the value
+    pub fn uncertainty(&self) -> N {
+        self.sources.values().fold(N::zero(), |sum, &c| sum + c*c).sqrt()
+    }
+    /// Makes `value` with the uncertainty of this changed by `slope`
+    fn scaled(&self, value: Value<N>, slope: N) -> Self {
+        Uncertain {
+            value,
+            sources: self.sources.iter().map(|(&id, &c)| (id, c*slope)).collect(),
+        }
+    }
+    /// Makes `value` from this and `rhs` given how much it changes with each of them
+    fn propagate(&self, rhs: &Self, value: Value<N>, slope: N, rhs_slope: N) -> Self {
+        let mut ret = self.scaled(value, slope);
+        for (&id, &c) in &rhs.sources {
+            let source = ret.sources.entry(id).or_insert_with(N::zero);
+            *source = *source + c*rhs_slope;
+        }
+        ret
+    }
+    /// Converts a value in an affine or logarithmic unit to the same value in base units
+    ///
+    /// Values in other units are returned as they are
+    pub fn absolute(&self) -> Self {
+        let value = self.value.absolute();
+        if value == self.value {
+            self.clone()
+        } else {
+            self.scaled(value, self.value.1.base_slope(self.value.0))
+        }
+    }
+    /// Adds or subtracts `rhs` with `op`, where `sign` is how the result changes with `rhs`
+    fn add_with<F>(&self, rhs: &Self, op: F, sign: N) -> Result<Self, UnitError>
+    where F: Fn(Value<N>, Value<N>) -> Result<Value<N>, UnitError> {
+        let value = op(self.value, rhs.value)?;
+        let (a, b) = (self.value, rhs.value);
+        let (slope, rhs_slope) = if a.1.is_logarithmic() || b.1.is_logarithmic() {
+            let level = |a, b| op(a, b).map_or(N::nan(), |v| v.0);
+            (derivative(|x| level(Value(x, a.1), b), a.0), derivative(|x| level(a, Value(x, b.1)), b.0))
+        } else {
+            (a.1.factor / value.1.factor, sign * b.1.factor / value.1.factor)
+        };
+        Ok(self.propagate(rhs, value, slope, rhs_slope))
+    }
+    /// Adds two values, failing like `Value::checked_add`
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, UnitError> {
+        self.add_with(rhs, Value::checked_add, N::one())
+    }
+    /// Subtracts `rhs` from this value, failing like `Value::checked_sub`
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self, UnitError> {
+        self.add_with(rhs, Value::checked_sub, -N::one())
+    }
+    /// Raises the value to an integer power, failing like `Value::powi`
+    pub fn powi(&self, n: i16) -> Result<Self, UnitError> {
+        let a = self.absolute();
+        let x = a.value.0;
+        let slope = N::from(n).unwrap() * x.powi(n as i32 - 1);
+        Ok(a.scaled(a.value.powi(n)?, slope))
+    }
+    /// Takes the square root of the value, failing like `Value::sqrt`
+    ///
+    /// The slope of the square root is infinite at zero, so there the uncertainty
+    /// is the square root of the uncertainty, like the largest root within it
+    pub fn sqrt(&self) -> Result<Self, UnitError> {
+        let a = self.absolute();
+        let root = a.value.sqrt()?;
+        let uncertainty = a.uncertainty();
+        let slope = if root.0 != N::zero() {
+            N::from(0.5).unwrap() / root.0
+        } else if uncertainty != N::zero() {
+            uncertainty.sqrt() / uncertainty
+        } else {
+            N::zero()
+        };
+        Ok(a.scaled(root, slope))
+    }
+    /// Takes the natural logarithm of the value, failing if it isn't dimensionless
+    pub fn ln(&self) -> Result<Self, UnitError> {
+        let a = self.absolute();
+        let Value(x, unit) = a.value;
+        if unit.dimension != NUL {
+            return Err(UnitError::DimensionMismatch(unit.dimension, NUL));
+        }
+        let x = unit.to_base(x);
+        Ok(a.scaled(Value(x.ln(), Unit::new(NUL)), unit.factor / x))
+    }
+    /// Applies a function to the number of the value giving a number in `unit`
+    ///
+    /// Values in affine or logarithmic units are made absolute first, so `f` gets their number in base units.
+    /// `df` is the derivative of `f`, used for propagating the uncertainty
+    pub fn map<F, D>(&self, f: F, df: D, unit: Unit<N>) -> Self
+    where F: Fn(N) -> N, D: Fn(N) -> N {
+        let a = self.absolute();
+        let x = a.value.0;
+        a.scaled(Value(f(x), unit), df(x))
+    }
+}
+
+impl<N: Float> From<Value<N>> for Uncertain<N> {
+    fn from(value: Value<N>) -> Self {
+        Uncertain::exact(value)
+    }
+}
+
+impl<N: Float> Add for Uncertain<N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(&rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<N: Float> Sub for Uncertain<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.checked_sub(&rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<N: Float> Mul for Uncertain<N> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self.absolute(), rhs.absolute());
+        a.propagate(&b, a.value * b.value, b.value.0, a.value.0)
+    }
+}
+
+impl<N: Float> Div for Uncertain<N> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let (a, b) = (self.absolute(), rhs.absolute());
+        let (x, y) = (a.value.0, b.value.0);
+        a.propagate(&b, a.value / b.value, y.recip(), -x / (y*y))
+    }
+}
+
+impl<N: Float> Mul<N> for Uncertain<N> {
+    type Output = Self;
+    fn mul(self, rhs: N) -> Self {
+        let a = self.absolute();
+        a.scaled(a.value * rhs, rhs)
+    }
+}
+
+impl<N: Float> Div<N> for Uncertain<N> {
+    type Output = Self;
+    fn div(self, rhs: N) -> Self {
+        let a = self.absolute();
+        a.scaled(a.value / rhs, rhs.recip())
+    }
+}
+
+#[test]
+fn uncertainty_test() {
+    let si = UnitSystem::<f64>::si();
+    let g = Uncertain::new(si.val(9.81, "m/s²").unwrap(), 0.02);
+    let t = Uncertain::new(si.val(2., "s").unwrap(), 0.01);
+
+    let v = g.clone() * t.clone();
+    assert_eq!(v.value, si.val(19.62, "m/s").unwrap());
+    assert_eq!(v.uncertainty(), ((0.02f64*2.).powi(2) + (9.81f64*0.01).powi(2)).sqrt());
+    assert_eq!((g.clone() - g.clone()).uncertainty(), 0.);
+    assert_eq!((g.clone() + g.clone()).uncertainty(), 0.04);
+    assert_eq!((g.clone() * 2.).uncertainty(), 0.04);
+    assert!((v.clone() / t.clone() - g.clone()).uncertainty() < 1e-12);
+
+    let sum = Uncertain::new(si.val(1., "km").unwrap(), 0.01) + Uncertain::new(si.val(3., "m").unwrap(), 4.);
+    assert_eq!(sum.value, si.val(1.003, "km").unwrap());
+    assert_eq!(sum.uncertainty(), 0.01f64.hypot(0.004));
+
+    assert_eq!(t.powi(2).unwrap().uncertainty(), 0.04);
+    assert_eq!(t.powi(2).unwrap().sqrt().unwrap().uncertainty(), 0.01);
+    assert_eq!(t.sqrt().unwrap_err(), UnitError::FractionalRoot(TIME, 2));
+    let huge = Uncertain::new(si.val(1., "m^20000").unwrap(), 0.1);
+    assert_eq!(huge.powi(2).unwrap_err(), UnitError::ExponentOverflow);
+    let zero = Uncertain::new(si.val(0., "m²").unwrap(), 0.04);
+    assert_eq!(zero.sqrt().unwrap().value, si.val(0., "m").unwrap());
+    assert!((zero.sqrt().unwrap().uncertainty() - 0.2).abs() < 1e-12);
+    assert_eq!(Uncertain::exact(si.val(0., "m²").unwrap()).sqrt().unwrap().uncertainty(), 0.);
+    let celsius = Uncertain::new(si.val(10., "°C").unwrap(), 0.5);
+    let kelvin = celsius.map(|x| x, |_| 1., Unit::new(TEMPERATURE));
+    assert_eq!(kelvin.value, si.val(283.15, "K").unwrap());
+    assert_eq!(kelvin.uncertainty(), 0.5);
+    assert!(t.ln().is_err());
+    assert_eq!((t.clone() / Uncertain::exact(si.val(1., "s").unwrap())).ln().unwrap().uncertainty(), 0.005);
+    assert_eq!(t.map(f64::exp, f64::exp, Unit::new(NUL)).uncertainty(), 2f64.exp() * 0.01);
+
+    let level = Uncertain::new(si.val(10., "dBm").unwrap(), 0.5) + Uncertain::exact(si.val(3., "dB").unwrap());
+    assert!((level.uncertainty() - 0.5).abs() < 1e-6);
+}
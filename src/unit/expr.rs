@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use pcm_calc::*;
+
+use tokeniser::{Token, Tokeniser, is_superscript};
+
+/// An expression typed into the REPL
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A number with a unit, like `3 km` or `2`
+    Literal(Value<f64>),
+    /// A variable or a unit, like `x` or `km`
+    Name(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    /// A function applied to its arguments, like `p(x)`
+    Call(String, Vec<Expr>),
+}
+
+/// Words that are part of the unit of a literal without being units, like in `3 metres per second`
+const UNIT_WORDS: &[&str] = &["per", "square", "cubic", "squared", "cubed"];
+
+/// Splits `s` into tokens, failing at the first text that isn't a token
+pub fn tokenise<'a>(s: &'a str) -> Result<Vec<(Token<'a>, Range<usize>)>, String> {
+    Tokeniser::new(s)
+        .map(|t| t.map_err(|span| format!("Unexpected `{}' at {}", &s[span.clone()], span.start)))
+        .collect()
+}
+
+/// Recursive descent parser for REPL expressions
+///
+/// The grammar is:
+///
+/// ```text
+/// expr    = term {("+" | "-") term}
+/// term    = unary {("*" | "·" | "/") unary}
+/// unary   = "-" unary | power
+/// power   = primary ["^" unary]
+/// primary = number [unit] | name "(" [expr {"," expr}] ")" | name | "(" expr ")"
+/// ```
+///
+/// A number directly followed by units is a single literal, so `6 m / 2 s` is 3 m/s.
+/// The unit is read like `UnitSystem::unit_from_str` and goes on for as long as there are
+/// units, so names after a number are units even if there is a variable by that name.
+struct Parser<'a> {
+    sys: &'a UnitSystem<f64>,
+    src: &'a str,
+    tokens: Vec<(Token<'a>, Range<usize>)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.peek_nth(0)
+    }
+    fn peek_nth(&self, n: usize) -> Option<Token<'a>> {
+        self.tokens.get(self.pos + n).map(|t| t.0)
+    }
+    fn bump(&mut self) -> Option<Token<'a>> {
+        let token = self.peek()?;
+        self.pos += 1;
+        Some(token)
+    }
+    /// Whether the next token is `token`, which is then skipped
+    fn eat(&mut self, token: Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+    fn unexpected(&self) -> String {
+        match self.tokens.get(self.pos) {
+            Some((_, span)) => format!("Unexpected `{}' at {}", &self.src[span.clone()], span.start),
+            None => "Unexpected end of expression".to_owned(),
+        }
+    }
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            expr = if self.eat(Token::Plus) {
+                Expr::Add(Box::new(expr), Box::new(self.term()?))
+            } else if self.eat(Token::Minus) {
+                Expr::Sub(Box::new(expr), Box::new(self.term()?))
+            } else {
+                return Ok(expr)
+            };
+        }
+    }
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            expr = if self.eat(Token::Times) {
+                Expr::Mul(Box::new(expr), Box::new(self.unary()?))
+            } else if self.eat(Token::Divide) {
+                Expr::Div(Box::new(expr), Box::new(self.unary()?))
+            } else {
+                return Ok(expr)
+            };
+        }
+    }
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(Token::Minus) {
+            return Ok(Expr::Neg(Box::new(self.unary()?)))
+        }
+        self.power()
+    }
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.primary()?;
+        if self.eat(Token::Caret) {
+            return Ok(Expr::Pow(Box::new(base), Box::new(self.unary()?)))
+        }
+        Ok(base)
+    }
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::LParen) => {
+                self.bump();
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Number(n)) => {
+                self.bump();
+                let unit = self.unit()?;
+                Ok(Expr::Literal(Value(n, unit)))
+            }
+            Some(Token::Ident(name)) => {
+                self.bump();
+                let name = name.to_owned();
+                if !self.eat(Token::LParen) {
+                    return Ok(Expr::Name(name))
+                }
+                let mut args = Vec::new();
+                if self.eat(Token::RParen) {
+                    return Ok(Expr::Call(name, args))
+                }
+                loop {
+                    args.push(self.expr()?);
+                    if self.eat(Token::RParen) {
+                        return Ok(Expr::Call(name, args))
+                    }
+                    self.expect(Token::Comma)?;
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+    /// Whether the token after the next `n` is part of a unit
+    fn is_unit_word(&self, n: usize) -> bool {
+        match self.peek_nth(n) {
+            Some(Token::Ident(word)) => {
+                let word = word.trim_end_matches(is_superscript).trim_end_matches(|c: char| c.is_ascii_digit());
+                self.sys.get_unit(word).is_some() || UNIT_WORDS.contains(&word)
+            }
+            _ => false,
+        }
+    }
+    /// Reads the unit following a number, which is dimensionless if there is none
+    fn unit(&mut self) -> Result<Unit<f64>, String> {
+        if !self.is_unit_word(0) {
+            return Ok(Unit::new(NUL))
+        }
+        let start = self.tokens[self.pos].1.start;
+        loop {
+            self.bump();
+            // Exponents like `m^2`, `m^-1` or `m^(1/2)`
+            if self.peek() == Some(Token::Caret) {
+                match (self.peek_nth(1), self.peek_nth(2)) {
+                    (Some(Token::Number(_)), _) => self.pos += 2,
+                    (Some(Token::Minus), Some(Token::Number(_))) => self.pos += 3,
+                    (Some(Token::LParen), _) => {
+                        self.pos += 2;
+                        while self.bump().is_some_and(|t| t != Token::RParen) {}
+                    }
+                    _ => (),
+                }
+            }
+            // Only go on after `/`, `·` or `*` if another unit follows
+            if let Some(Token::Divide) | Some(Token::Times) = self.peek() {
+                if self.is_unit_word(1) {
+                    self.bump();
+                }
+            }
+            if !self.is_unit_word(0) {
+                break
+            }
+        }
+        let end = self.tokens[self.pos - 1].1.end;
+        self.sys.unit_from_str(&self.src[start..end]).map_err(|e| e.to_string())
+    }
+}
+
+/// Parses an expression
+pub fn parse(s: &str, sys: &UnitSystem<f64>) -> Result<Expr, String> {
+    let mut parser = Parser {sys, src: s, tokens: tokenise(s)?, pos: 0};
+    let expr = parser.expr()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected())
+    }
+    Ok(expr)
+}
+
+/// Evaluates an expression, where names are variables or else units
+pub fn evaluate(expr: &Expr, vars: &HashMap<String, Value<f64>>, sys: &UnitSystem<f64>) -> Result<Value<f64>, String> {
+    let eval = |e: &Expr| evaluate(e, vars, sys);
+    Ok(match *expr {
+        Expr::Literal(val) => val,
+        Expr::Name(ref name) => match vars.get(name) {
+            Some(&val) => val,
+            None => Value(1., sys.get_unit(name).ok_or_else(|| format!("No such variable or unit `{}'", name))?),
+        },
+        Expr::Neg(ref a) => {
+            let Value(v, u) = eval(a)?;
+            Value(-v, u)
+        }
+        Expr::Add(ref a, ref b) => eval(a)?.checked_add(eval(b)?).map_err(|e| e.to_string())?,
+        Expr::Sub(ref a, ref b) => eval(a)?.checked_sub(eval(b)?).map_err(|e| e.to_string())?,
+        Expr::Mul(ref a, ref b) => eval(a)?.checked_mul(eval(b)?).map_err(|e| e.to_string())?,
+        Expr::Div(ref a, ref b) => eval(a)?.checked_div(eval(b)?).map_err(|e| e.to_string())?,
+        Expr::Pow(ref a, ref b) => {
+            let Value(e, u) = eval(b)?.absolute();
+            if u.dimension != NUL {
+                return Err(format!("Exponent with dimension {:#}", u.dimension))
+            }
+            let e = u.to_base(e);
+            let Value(n, base) = eval(a)?.absolute();
+            // Dimensionless numbers can be raised to any power
+            if base.dimension == NUL {
+                return Ok(Value(base.to_base(n).powf(e), Unit::new(NUL)))
+            }
+            let e = Exponent::approximate(e).ok_or_else(|| format!("Bad exponent `{}' for {:#}", e, base.dimension))?;
+            Value(n, base).pow(e).map_err(|e| e.to_string())?
+        }
+        Expr::Call(ref f, ref args) => {
+            let args = args.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+            func(f, &args, sys)?
+        }
+    })
+}
+
+type Unary = fn(Value<f64>) -> Result<Value<f64>, UnitError>;
+type Binary = fn(Value<f64>, Value<f64>) -> Result<Value<f64>, UnitError>;
+type Inverse = fn(Value<f64>, Dimension) -> Result<Value<f64>, UnitError>;
+
+/// Functions of one value besides `p` and the inverse trigonometric ones, which need the unit system
+const UNARY: &[(&str, Unary)] = &[
+    ("sqrt", Value::sqrt), ("cbrt", Value::cbrt), ("abs", |v| Ok(v.abs())),
+    ("exp", Value::exp), ("ln", Value::ln), ("log", Value::log10), ("log10", Value::log10),
+    ("sin", Value::sin), ("cos", Value::cos), ("tan", Value::tan),
+];
+/// Inverse trigonometric functions, which give angles of the dimension angles have in the system
+const INVERSE: &[(&str, Inverse)] = &[("asin", Value::asin), ("acos", Value::acos), ("atan", Value::atan)];
+const BINARY: &[(&str, Binary)] = &[
+    ("hypot", Value::hypot), ("min", Value::min), ("max", Value::max),
+];
+
+/// Applies the function `f`, checking the dimensions of the arguments
+fn func(f: &str, args: &[Value<f64>], sys: &UnitSystem<f64>) -> Result<Value<f64>, String> {
+    let result = if f == "p" {
+        match *args {
+            [val] => sys.try_as(val, "pH"),
+            _ => return Err(format!("`{}' takes one argument", f)),
+        }
+    } else if f == "atan2" {
+        match *args {
+            [y, x] => y.atan2(x, sys.angle()),
+            _ => return Err(format!("`{}' takes two arguments", f)),
+        }
+    } else if let Some(&(_, inverse)) = INVERSE.iter().find(|&&(name, _)| name == f) {
+        match *args {
+            [val] => inverse(val, sys.angle()),
+            _ => return Err(format!("`{}' takes one argument", f)),
+        }
+    } else if let Some(&(_, unary)) = UNARY.iter().find(|&&(name, _)| name == f) {
+        match *args {
+            [val] => unary(val),
+            _ => return Err(format!("`{}' takes one argument", f)),
+        }
+    } else if let Some(&(_, binary)) = BINARY.iter().find(|&&(name, _)| name == f) {
+        match *args {
+            [a, b] => binary(a, b),
+            _ => return Err(format!("`{}' takes two arguments", f)),
+        }
+    } else {
+        return Err(format!("No such function `{}'", f))
+    };
+    result.map_err(|e| e.to_string())
+}
+
+#[test]
+fn expression_test() {
+    let mut si = UnitSystem::si();
+    si.add_constants();
+    let mut vars = HashMap::new();
+    vars.insert("x".to_owned(), si.val(2., "s").unwrap());
+    let eval = |s: &str| parse(s, &si).and_then(|e| evaluate(&e, &vars, &si));
+    let check = |s: &str, expected: &str| {
+        let (val, expected) = (eval(s).unwrap(), si.val_s(expected).unwrap());
+        assert_eq!(val.1.dimension, expected.1.dimension, "{}", s);
+        assert!((val.1.to_base(val.0) - expected.1.to_base(expected.0)).abs() < 1e-9, "{} is {:?}", s, val);
+    };
+
+    check("3 km / (20 min) + 5 m/s", "7.5 m/s");
+    check("1 + 2 * 3 ^ 2", "19");
+    check("-(2 m)^2", "-4 m²");
+    check("6 m / 2 s", "3 m/s");
+    check("10 m / x", "5 m/s");
+    check("(4 m^2)^(1/2)", "2 m");
+    check("5 metres per second - km/h", "17 km/h");
+    check("sqrt(9 km^2) + max(1 m, 2 m)", "3002 m");
+    check("hypot(3 m, 400 cm)", "5 m");
+    check("h_P * 1e34 Hz", "6.62607015 J");
+    check("2 h / (30 min)", "4");
+    assert_eq!(parse("p(x)", &si).unwrap(), Expr::Call("p".to_owned(), vec![Expr::Name("x".to_owned())]));
+    assert_eq!(eval("sqrt(x)").unwrap_err(), format!("Root 2 of {:#} would have fractional exponents", TIME));
+    assert_eq!(eval("min(1 m)").unwrap_err(), "`min' takes two arguments");
+    assert_eq!(eval("foo(1)").unwrap_err(), "No such function `foo'");
+    assert!(eval("1 m + 1 s").is_err());
+    assert!(eval("2 x").is_err());
+    assert_eq!(eval("(1 m").unwrap_err(), "Unexpected end of expression");
+    assert_eq!(eval("1 + * 2").unwrap_err(), "Unexpected `*' at 4");
+    assert_eq!(eval("2 % 3").unwrap_err(), "Unexpected `%' at 2");
+
+    check("2^0.5 * 2^0.5", "2");
+    check("(8 m / 2 m)^0.5", "2");
+    check("3 m2", "3 m²");
+    assert_eq!(eval("(1 m)^30000 * (1 m)^30000").unwrap_err(), "Exponent of dimension too large");
+    assert_eq!(eval("(1 m)^30000 / (1 m)^-30000").unwrap_err(), "Exponent of dimension too large");
+    assert_eq!(eval("(1 m^2)^30000").unwrap_err(), "Exponent of dimension too large");
+    assert_eq!(eval("1 m^20000 m^20000").unwrap_err(), "Exponent of dimension too large in `m^20000 m^20000'");
+    assert!(eval("(1 m)^0.123").is_err());
+
+    let angles = UnitSystem::si_with_angles();
+    let angle = parse("atan2(1 m, 1 m)", &angles).and_then(|e| evaluate(&e, &vars, &angles)).unwrap();
+    assert!((angles.as_(angle, "deg").0 - 45.).abs() < 1e-9);
+}
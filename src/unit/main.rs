@@ -6,87 +6,120 @@ use std::collections::HashMap;
 use pcm_calc::*;
 
 mod tokeniser;
+mod expr;
 
-#[derive(Debug)]
-enum Eval {
-    Add(String, String),
-    Sub(String, String),
-    Mul(String, String),
-    Div(String, String),
-    Pow(String, String),
-    Func(String, String)
-}
+use expr::{Expr, evaluate};
+use tokeniser::Token;
 
 #[derive(Debug)]
 enum Command {
-    Define(String, Value<f64>),
-    Eval(Eval),
+    Assign(String, Expr),
+    DefineUnit(String, Expr),
+    Eval(Expr),
+    Convert(Expr, Vec<String>),
     Inspect(String),
-    Assign(String, Eval)
 }
 
-// TODO split better
-fn line_to_command(s: &str, si: &UnitSystem<f64>) -> Option<Command> {
-    let mut words = s.split_whitespace().filter(|s| !s.is_empty());
-    let first_arg = words.next()?.to_owned();
-
-    Some(match words.next()? {
-        "=" | ":=" => {
-            let s = words.collect::<Vec<_>>().join(" ");
-            let cmd = line_to_command(&s, si);
-            if let Some(Command::Eval(eval)) = cmd {
-                Command::Assign(first_arg, eval)
-            } else {
-                Command::Define(first_arg, si.val_s(&s)?)
-            }
+fn is_name(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_alphabetic() || c == '_') && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn line_to_command(s: &str, si: &UnitSystem<f64>) -> Result<Command, String> {
+    let s = s.trim();
+    if let Some(name) = s.strip_suffix('?') {
+        let name = name.trim();
+        if is_name(name) {
+            return Ok(Command::Inspect(name.to_owned()));
         }
-        "?" => Command::Inspect(first_arg),
-        "+" => Command::Eval(Eval::Add(first_arg, words.next()?.to_owned())),
-        "-" => Command::Eval(Eval::Sub(first_arg, words.next()?.to_owned())),
-        "*" => Command::Eval(Eval::Mul(first_arg, words.next()?.to_owned())),
-        "/" => Command::Eval(Eval::Div(first_arg, words.next()?.to_owned())),
-        "^" => Command::Eval(Eval::Pow(first_arg, words.next()?.to_owned())),
-        snd => if first_arg.chars().all(<char>::is_alphabetic) {
-            Command::Eval(Eval::Func(first_arg, snd.to_owned()))
+    }
+    if let Some(i) = s.find('=') {
+        let (lhs, rhs) = (s[..i].trim_end_matches(':').trim(), &s[i+1..]);
+        let (unit, lhs) = match lhs.strip_prefix("unit ") {
+            Some(name) => (true, name.trim()),
+            None => (false, lhs),
+        };
+        if !is_name(lhs) {
+            return Err(format!("Can't assign to `{}'", lhs));
+        }
+        if unit && si.get_unit(lhs).is_some() {
+            return Err(format!("`{}' is already a unit", lhs));
+        }
+        let expr = expr::parse(rhs, si)?;
+        return Ok(if unit {
+            Command::DefineUnit(lhs.to_owned(), expr)
         } else {
-            return None;
+            Command::Assign(lhs.to_owned(), expr)
+        });
+    }
+    let (lhs, targets) = split_conversion(s, si);
+    let expr = expr::parse(lhs, si)?;
+    match targets {
+        Some(targets) => {
+            let targets: Vec<_> = targets.split(',').map(|t| t.trim().to_owned()).collect();
+            if targets.iter().any(String::is_empty) {
+                return Err(format!("Missing unit to convert to in `{}'", s));
+            }
+            Ok(Command::Convert(expr, targets))
         }
-    })
+        None => Ok(Command::Eval(expr)),
+    }
 }
 
-fn evaluate(eval: Eval, vars: &HashMap<String, Value<f64>>) -> Option<Value<f64>> {
-    let get_or_eval = |n: String| {
-        vars.get(&n).cloned()
-            .or_else(|| n.parse().ok().and_then(|v| Some( Value(v, Unit::new(NUL)) ) ) )
+/// Splits `expr -> units` or `expr in units` into the expression and the units
+///
+/// Only a `->` or `in` outside of parentheses counts. An `in` only counts if everything
+/// after it is a list of units, taking the last such one, so `3 in in cm` converts
+/// three inches and `3 in + 1 ft` is a sum, since there is a unit `in`
+fn split_conversion<'a>(s: &'a str, si: &UnitSystem<f64>) -> (&'a str, Option<&'a str>) {
+    let tokens = match expr::tokenise(s) {
+        Ok(tokens) => tokens,
+        Err(_) => return (s, None),
     };
-
-    Some(match eval {
-        Eval::Add(a, b) => get_or_eval(a)? + get_or_eval(b)?,
-        Eval::Sub(a, b) => get_or_eval(a)? - get_or_eval(b)?,
-        Eval::Mul(a, b) => get_or_eval(a)? * get_or_eval(b)?,
-        Eval::Div(a, b) => get_or_eval(a)? / get_or_eval(b)?,
-        Eval::Pow(a, b) => {
-            let n: i16 = b.parse().ok()?;
-            let Value(v, u) = get_or_eval(a)?;
-
-            Value(v.powi(n as i32), u*n)
-        },
-        Eval::Func(a, b) => func(&a, *vars.get(&b)?),
-    })
+    let is_units = |targets: &str| targets.split(',').all(|t| !t.trim().is_empty() && si.unit_from_str(t).is_ok());
+    let mut depth = 0;
+    let mut split = None;
+    for (i, &(token, ref span)) in tokens.iter().enumerate() {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth -= 1,
+            _ if depth != 0 => (),
+            Token::Arrow => return (&s[..span.start], Some(&s[span.end..])),
+            Token::Ident("in") if i > 0 && is_units(&s[span.end..]) => split = Some(span.clone()),
+            _ => (),
+        }
+    }
+    match split {
+        Some(span) => (&s[..span.start], Some(&s[span.end..])),
+        None => (s, None),
+    }
 }
 
-fn func(f: &str, val: Value<f64>) -> Value<f64> {
-    match f {
-        "p" => {
-            assert_eq!(val.1.dimension, CONCENTRATION);
-            Value(-(val.0 * val.1.factor/1e3).log10(), Unit::new(NUL))
-        }
-        _ => panic!("No such function `{}'", f)
+/// Writes a value in the given units, or as a sum of them if there are several
+fn convert(val: Value<f64>, targets: &[String], si: &UnitSystem<f64>) -> Result<String, String> {
+    let mismatch = |e| match e {
+        UnitError::DimensionMismatch(a, b) => format!("Can't convert {:#} to {:#}", a, b),
+        e => e.to_string(),
+    };
+    if let [ref target] = *targets {
+        let val = si.try_as(val, target).map_err(mismatch)?;
+        let display = si.display(&val).prefer(&[target]).map_err(|e| e.to_string())?;
+        Ok(display.to_string())
+    } else {
+        let targets: Vec<_> = targets.iter().map(String::as_str).collect();
+        let display = si.display(&val).compound(&targets).map_err(mismatch)?;
+        Ok(display.to_string())
     }
 }
 
+/// The units and constants the REPL reads, which are displayed in SI units
+fn system() -> UnitSystem<f64> {
+    let mut sys = UnitSystem::si_with_customary_units();
+    sys.add_constants();
+    sys
+}
+
 fn main() {
-    let si = UnitSystem::<f64>::si();
+    let mut si = system();
     let mut vars = HashMap::new();
 
     let mut s = String::new();
@@ -104,40 +137,89 @@ fn main() {
             }
         }
 
-        if let Some(c) = line_to_command(&s, &si) {
-            match c {
-                Command::Define(name, val) => {
-                    vars.insert(name, val);
-                }
-                Command::Eval(eval) => {
-                    if let Some(val) = evaluate(eval, &vars) {
-                        println!("= {}", si.display(&val));
-                    } else {
-                        println!("No such variable");
-                    }
-                }
-                Command::Assign(name, eval) => {
-                    if let Some(val) = evaluate(eval, &vars) {
+        if s == "stop" {
+            break
+        }
+        match line_to_command(&s, &si) {
+            Ok(Command::Assign(name, expr)) => {
+                match evaluate(&expr, &vars, &si) {
+                    Ok(val) => {
                         println!("= {}", si.display(&val));
                         vars.insert(name, val);
-                    } else {
-                        println!("No such variable");
                     }
+                    Err(e) => println!("{}", e),
                 }
-                Command::Inspect(name) => {
-                    if let Some(val) = vars.get(&name) {
-                        println!("= {} ({:#})", si.display(&val), val.1.dimension);
-                    } else {
-                        println!("No such variable");
+            }
+            Ok(Command::DefineUnit(name, expr)) => {
+                match evaluate(&expr, &vars, &si) {
+                    Ok(val) => {
+                        let Value(v, u) = val.absolute();
+                        si.add_unit(name, Unit::with_factor(u.dimension, v * u.factor));
                     }
+                    Err(e) => println!("{}", e),
                 }
             }
-        } else {
-            if s == "stop" {
-                break
+            Ok(Command::Eval(expr)) => {
+                match evaluate(&expr, &vars, &si) {
+                    Ok(val) => println!("= {}", si.display(&val)),
+                    Err(e) => println!("{}", e),
+                }
+            }
+            Ok(Command::Convert(expr, targets)) => {
+                match evaluate(&expr, &vars, &si).and_then(|val| convert(val, &targets, &si)) {
+                    Ok(s) => println!("= {}", s),
+                    Err(e) => println!("{}", e),
+                }
+            }
+            Ok(Command::Inspect(name)) => {
+                if let Some(val) = vars.get(&name) {
+                    println!("= {} ({:#})", si.display(val), val.1.dimension);
+                } else {
+                    println!("No such variable");
+                }
             }
-            println!("Bad query");
+            Err(e) => println!("{}", e),
         }
         s.clear();
     }
 }
+
+#[test]
+fn conversion_test() {
+    let si = system();
+    let mut vars = HashMap::new();
+    vars.insert("x".to_owned(), si.val(3.048, "m").unwrap());
+    let run = |s: &str| match line_to_command(s, &si)? {
+        Command::Convert(expr, targets) => convert(evaluate(&expr, &vars, &si)?, &targets, &si),
+        c => panic!("{:?}", c),
+    };
+
+    assert_eq!(run("36 km/h -> m/s").unwrap(), "10 m/s");
+    assert_eq!(run("90 min in h").unwrap(), "1.5 h");
+    assert_eq!(run("(2 h + 30 min) in min").unwrap(), "150 min");
+    assert_eq!(run("5000 s -> h, min, s").unwrap(), "1 h 23 min 20 s");
+    assert_eq!(run("1 m -> s").unwrap_err(), format!("Can't convert {:#} to {:#}", LENGTH, TIME));
+    assert_eq!(run("1 m -> h, s").unwrap_err(), format!("Can't convert {:#} to {:#}", LENGTH, TIME));
+    assert!(run("1 m ->").is_err());
+    assert_eq!(run("x in ft").unwrap(), "10 ft");
+    assert_eq!(run("x + 1 in -> ft, in").unwrap(), "10 ft 1 in");
+    // The last `in` followed by a unit is the conversion
+    assert_eq!(run("10 in in cm").unwrap(), "25.4 cm");
+    assert_eq!(run("1 yd in in").unwrap(), "36 in");
+
+    // An `in` followed by anything but units is the unit inch
+    let inches = |s: &str| match line_to_command(s, &si) {
+        Ok(Command::Eval(expr)) => si.as_(evaluate(&expr, &vars, &si).unwrap(), "in").0,
+        c => panic!("{:?}", c),
+    };
+    assert!((inches("3 in + 1 ft") - 15.).abs() < 1e-9);
+    assert!((inches("2 in * 3") - 6.).abs() < 1e-9);
+    assert!((inches("(1 ft - 2 in) / 2 in * 1 in") - 5.).abs() < 1e-9);
+    assert_eq!(run("3 in + 1 ft in cm").unwrap(), "38.1 cm");
+
+    assert!(matches!(line_to_command("x1 = 3", &si), Ok(Command::Assign(ref name, _)) if name == "x1"));
+    assert_eq!(line_to_command("1x = 3", &si).unwrap_err(), "Can't assign to `1x'");
+    assert!(matches!(line_to_command("unit fur = 220 yd", &si), Ok(Command::DefineUnit(ref name, _)) if name == "fur"));
+    assert_eq!(line_to_command("unit m = 2 ft", &si).unwrap_err(), "`m' is already a unit");
+    assert_eq!(line_to_command("unit km = 2 ft", &si).unwrap_err(), "`km' is already a unit");
+}
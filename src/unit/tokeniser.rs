@@ -1,74 +1,131 @@
-use std::str::CharIndices;
+use std::ops::Range;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum Mode {
-    Numbers,
-    Letters,
-    Symbols,
-    Nothing
+use pcm_calc::is_unit_char;
+
+/// A token of REPL input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token<'a> {
+    /// A number like `3`, `0.5` or `1.2e-3`
+    Number(f64),
+    /// A name of a variable, unit or function, including any superscript exponent like `m²`
+    Ident(&'a str),
+    Plus,
+    Minus,
+    /// `*` or `·`
+    Times,
+    Divide,
+    Caret,
+    LParen,
+    RParen,
+    /// `->`
+    Arrow,
+    Comma,
+}
+
+/// Whether `c` can be part of a superscript exponent like the one in `m⁻¹` or `m¹ᐟ²`
+pub fn is_superscript(c: char) -> bool {
+    "⁰¹²³⁴⁵⁶⁷⁸⁹⁻ᐟ".contains(c)
 }
 
+/// Splits REPL input into tokens with their byte spans in the input
+///
+/// Whitespace only separates tokens. An error holds the span of the text that isn't a token.
 pub struct Tokeniser<'a> {
     string: &'a str,
-    chars: CharIndices<'a>,
-    start_index: usize,
-    cur_mode: Mode
+    pos: usize,
 }
 
 impl<'a> Tokeniser<'a> {
     pub fn new(s: &'a str) -> Self {
         Tokeniser {
-            start_index: 0,
             string: s,
-            chars: s.char_indices(),
-            cur_mode: Mode::Nothing
+            pos: 0,
         }
     }
-    fn char_mode(&mut self, c: char) {
-        if c.is_digit(10) {
-            self.cur_mode = Mode::Numbers;
-        } else if c.is_alphabetic() {
-            self.cur_mode = Mode::Letters;
-        } else if c.is_whitespace() {
-            self.cur_mode = Mode::Nothing;
-        } else {
-            self.cur_mode = Mode::Symbols;
+    fn peek(&self) -> Option<char> {
+        self.string[self.pos..].chars().next()
+    }
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) {
+        while self.peek().is_some_and(&f) {
+            self.pos += self.peek().unwrap().len_utf8();
+        }
+    }
+    /// Reads a number starting at `pos` into the exponent if there is one
+    fn number(&mut self) {
+        self.take_while(|c| c.is_ascii_digit());
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            self.take_while(|c| c.is_ascii_digit());
+        }
+        let mut exp = self.string[self.pos..].chars();
+        if let Some('e') | Some('E') = exp.next() {
+            let mut len = 1;
+            let mut next = exp.next();
+            if let Some('-') | Some('+') = next {
+                len += 1;
+                next = exp.next();
+            }
+            if next.is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += len;
+                self.take_while(|c| c.is_ascii_digit());
+            }
         }
     }
 }
 
-// TODO Broken tokeniser
-
 impl<'a> Iterator for Tokeniser<'a> {
-    type Item = &'a str;
+    type Item = Result<(Token<'a>, Range<usize>), Range<usize>>;
     fn next(&mut self) -> Option<Self::Item> {
-        let last_mode = self.cur_mode;
-        let (mut i, mut next_char) = self.chars.next()?;
-
-        self.char_mode(next_char);
+        self.take_while(char::is_whitespace);
+        let start = self.pos;
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
 
-        loop {
-            self.char_mode(next_char);
-            if let Some((j, c)) = self.chars.next() {
-                i = j;
-                next_char = c;
-            } else {
-                return Some(&self.string[self.start_index..])
+        let token = match c {
+            '+' => Token::Plus,
+            '-' if self.peek() == Some('>') => {
+                self.pos += 1;
+                Token::Arrow
             }
-
-            if let Mode::Nothing = self.cur_mode {
-                self.start_index = i;
-                continue;
+            '-' => Token::Minus,
+            '*' | '·' => Token::Times,
+            '/' => Token::Divide,
+            '^' => Token::Caret,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            c if c.is_ascii_digit() || c == '.' => {
+                self.pos = start;
+                self.number();
+                match self.string[start..self.pos].parse() {
+                    Ok(n) => Token::Number(n),
+                    Err(_) => return Some(Err(start..self.pos)),
+                }
             }
-
-            if last_mode != self.cur_mode {
-                break;
+            // Names may have digits after the first character, like `x1`
+            c if is_unit_char(c) => {
+                self.take_while(|c| is_unit_char(c) || c.is_ascii_digit());
+                self.take_while(is_superscript);
+                Token::Ident(&self.string[start..self.pos])
             }
-        }
+            _ => return Some(Err(start..self.pos)),
+        };
+        Some(Ok((token, start..self.pos)))
+    }
+}
 
-        let start = self.start_index;
-        self.start_index = i+next_char.len_utf8();
+#[test]
+fn tokeniser_test() {
+    use self::Token::*;
 
-        Some(&self.string[start..i])
-    }
+    let tokens: Vec<_> = Tokeniser::new("-(1.5e-3 m² ->km·s⁻¹), 2.").map(Result::unwrap).collect();
+    assert_eq!(tokens, vec![
+        (Minus, 0..1), (LParen, 1..2), (Number(1.5e-3), 2..8), (Ident("m²"), 9..12), (Arrow, 13..15),
+        (Ident("km"), 15..17), (Times, 17..19), (Ident("s⁻¹"), 19..25), (RParen, 25..26), (Comma, 26..27),
+        (Number(2.), 28..30),
+    ]);
+    let tokens: Vec<_> = Tokeniser::new("2e x_1 °C").map(|t| t.unwrap().0).collect();
+    assert_eq!(tokens, vec![Number(2.), Ident("e"), Ident("x_1"), Ident("°C")]);
+    assert_eq!(Tokeniser::new("1 % 2").nth(1), Some(Err(2..3)));
+    assert_eq!(Tokeniser::new(". 5").next(), Some(Err(0..1)));
 }
@@ -1,147 +1,606 @@
+use std::borrow::Cow;
 use std::ops::{Add, Sub, Mul, Div};
-use std::fmt::Debug;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use num::Float;
 
 use super::*;
 
 /// The base units for each dimension
+///
+/// Names are either borrowed from static strings, like those of `SI`, or owned
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BaseUnits {
     /// Base unit for length
-    pub length: &'static str,
+    pub length: Cow<'static, str>,
     /// Base unit for time
-    pub time: &'static str,
+    pub time: Cow<'static, str>,
     /// Base unit for mass
-    pub mass: &'static str,
+    pub mass: Cow<'static, str>,
     /// Base unit for current
-    pub current: &'static str,
+    pub current: Cow<'static, str>,
     /// Base unit for temperature
-    pub temperature: &'static str,
+    pub temperature: Cow<'static, str>,
     /// Base unit for amount of substance
-    pub substance_amount: &'static str,
+    pub substance_amount: Cow<'static, str>,
     /// Base unit for luminous intensity
-    pub luminous_intensity: &'static str,
+    pub luminous_intensity: Cow<'static, str>,
+    /// Base unit for plane angle, if the system keeps track of angles
+    pub angle: Option<Cow<'static, str>>,
+}
+
+impl BaseUnits {
+    /// The names of the base units, with that of plane angle if there is one
+    fn names_mut(&mut self) -> impl Iterator<Item = &mut Cow<'static, str>> {
+        let BaseUnits{length, time, mass, current, temperature, substance_amount, luminous_intensity, angle} = self;
+        vec![length, time, mass, current, temperature, substance_amount, luminous_intensity].into_iter().chain(angle.as_mut())
+    }
+    /// Whether `name` is one of the base units
+    pub fn contains(&self, name: &str) -> bool {
+        let BaseUnits{length, time, mass, current, temperature, substance_amount, luminous_intensity, angle} = self;
+        [length, time, mass, current, temperature, substance_amount, luminous_intensity].iter().any(|base| *base == name)
+            || angle.as_ref().is_some_and(|angle| angle == name)
+    }
 }
 
 /// The base units of SI
+#[allow(unused_doc_comments)]
 pub const SI: BaseUnits = BaseUnits {
     /// The SI base unit for length: metres
-    length: "m",
+    length: Cow::Borrowed("m"),
     /// The SI base unit for time: seconds
-    time: "s",
+    time: Cow::Borrowed("s"),
     /// The SI base unit for mass: kilogrammes
-    mass: "kg",
+    mass: Cow::Borrowed("kg"),
     /// The SI base unit for current: amperes
-    current: "A",
+    current: Cow::Borrowed("A"),
     /// The SI base unit for temperature: kelvin
-    temperature: "K",
+    temperature: Cow::Borrowed("K"),
     /// The SI base unit for amount of substance: moles
-    substance_amount: "mol",
+    substance_amount: Cow::Borrowed("mol"),
     /// The SI base unit for luminous intensity: candelas
-    luminous_intensity: "cd",
+    luminous_intensity: Cow::Borrowed("cd"),
+    /// SI has no base unit for plane angle, since angles are dimensionless
+    angle: None,
 };
 
 /// A collection of units and their ratios to each other
 ///
-/// Used for writing and reading units from and to strings
+/// Used for writing and reading units from and to strings.
+/// Names are `Cow`s, so the built-in units borrow static strings
+/// while units defined at runtime own their names
 pub struct UnitSystem<N: Float> {
     /// The set of base units for this system
     pub base: BaseUnits,
     /// Derivative units for this system and their relation to the base units
-    pub units: HashMap<&'static str, Unit<N>>
+    pub units: HashMap<Cow<'static, str>, Unit<N>>,
+    /// Names of the units that accept an SI prefix
+    pub prefixable: HashSet<Cow<'static, str>>,
+    /// Units to display values of a dimension in, in order of preference
+    pub preferred: HashMap<Dimension, Vec<(String, Unit<N>)>>,
+    /// Physical constants that can be read like units, but are never used for displaying values
+    pub constants: HashMap<Cow<'static, str>, Unit<N>>,
+    /// Long names of units by their symbols
+    pub names: HashMap<Cow<'static, str>, UnitNames>,
+    /// Other names units can be read by, like `meter` for `m`, mapped to their symbols
+    pub aliases: HashMap<Cow<'static, str>, Cow<'static, str>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The long names of a unit, like `metre` and `metres`
+pub struct UnitNames {
+    /// The name of one of the unit
+    pub singular: Cow<'static, str>,
+    /// The name of any other number of the unit
+    pub plural: Cow<'static, str>,
 }
 
 impl<N: Float> UnitSystem<N> {
     /// Creates a new `UnitSystem` from a base with a specified capacity
     pub fn new_base_with_cap(base: BaseUnits, cap: usize) -> Self {
-        let mut units = HashMap::with_capacity(7+cap);
-        units.insert(base.length, Unit::new(LENGTH));
-        units.insert(base.time, Unit::new(TIME));
-        units.insert(base.mass, Unit::new(MASS));
-        units.insert(base.current, Unit::new(CURRENT));
-        units.insert(base.temperature, Unit::new(TEMPERATURE));
-        units.insert(base.substance_amount, Unit::new(AMOUNT_OF_SUBSTANCE));
-        units.insert(base.luminous_intensity, Unit::new(LUMINOUS_INTENSITY));
+        let mut units = HashMap::with_capacity(8+cap);
+        units.insert(base.length.clone(), Unit::new(LENGTH));
+        units.insert(base.time.clone(), Unit::new(TIME));
+        units.insert(base.mass.clone(), Unit::new(MASS));
+        units.insert(base.current.clone(), Unit::new(CURRENT));
+        units.insert(base.temperature.clone(), Unit::new(TEMPERATURE));
+        units.insert(base.substance_amount.clone(), Unit::new(AMOUNT_OF_SUBSTANCE));
+        units.insert(base.luminous_intensity.clone(), Unit::new(LUMINOUS_INTENSITY));
+        if let Some(ref angle) = base.angle {
+            units.insert(angle.clone(), Unit::new(ANGLE));
+        }
 
         UnitSystem {
             base,
             units,
+            prefixable: HashSet::with_capacity(8+cap),
+            preferred: HashMap::new(),
+            constants: HashMap::new(),
+            names: HashMap::new(),
+            aliases: HashMap::new(),
         }
     }
     /// Creates a system with SI units
+    ///
+    /// Every unit except kg, min, h, the temperature scales, the logarithmic units
+    /// and the angles other than rad and sr accepts the SI prefixes.
+    /// Angles are dimensionless like in SI, so rad/s is the same as Hz
     pub fn si() -> Self {
-        let mut ret = Self::new_base_with_cap(SI, 16);
-
-        ret.units.insert("J", Unit::new(ENERGY));
-        ret.units.insert("min", Unit::with_factor(TIME, N::from(60).unwrap()));
-        ret.units.insert("h", Unit::with_factor(TIME, N::from(3600).unwrap()));
-        ret.units.insert("km", Unit::with_factor(LENGTH, N::from(1000).unwrap()));
-        ret.units.insert("g", Unit::with_factor(MASS, N::from(1e-3).unwrap()));
-        ret.units.insert("Hz", Unit::new(FREQUENCY));
-        ret.units.insert("L", Unit::with_factor(VOLUME, N::from(1e-3).unwrap()));
-        ret.units.insert("mL", Unit::with_factor(VOLUME, N::from(1e-6).unwrap()));
-        ret.units.insert("M", Unit::with_factor(CONCENTRATION, N::from(1e3).unwrap()));
-        ret.units.insert("N", Unit::new(FORCE));
-        ret.units.insert("kN", Unit::with_factor(FORCE, N::from(1e3).unwrap()));
-        ret.units.insert("W", Unit::new(POWER));
-        ret.units.insert("V", Unit::new(VOLTAGE));
-        ret.units.insert("mA", Unit::with_factor(CURRENT, N::from(1e-3).unwrap()));
-        ret.units.insert("Ω", Unit::new(RESISTANCE));
-        ret.units.insert("C", Unit::new(CHARGE));
-        ret.units.insert("Pa", Unit::new(PRESSURE));
+        let mut ret = Self::new_base_with_cap(SI, 24);
+        ret.prefixable.extend(["m", "s", "A", "K", "mol", "cd", "rad"].iter().map(|&name| Cow::Borrowed(name)));
+
+        ret.add_prefixable_unit("J", Unit::new(ENERGY));
+        ret.add_unit("min", Unit::with_factor(TIME, N::from(60).unwrap()));
+        ret.add_unit("h", Unit::with_factor(TIME, N::from(3600).unwrap()));
+        ret.add_prefixable_unit("g", Unit::with_factor(MASS, N::from(1e-3).unwrap()));
+        ret.add_prefixable_unit("Hz", Unit::new(FREQUENCY));
+        ret.add_prefixable_unit("L", Unit::with_factor(VOLUME, N::from(1e-3).unwrap()));
+        ret.add_prefixable_unit("M", Unit::with_factor(CONCENTRATION, N::from(1e3).unwrap()));
+        ret.add_prefixable_unit("N", Unit::new(FORCE));
+        ret.add_prefixable_unit("W", Unit::new(POWER));
+        ret.add_prefixable_unit("V", Unit::new(VOLTAGE));
+        ret.add_prefixable_unit("Ω", Unit::new(RESISTANCE));
+        ret.add_prefixable_unit("C", Unit::new(CHARGE));
+        ret.add_prefixable_unit("F", Unit::new(CAPACITANCE));
+        ret.add_prefixable_unit("Pa", Unit::new(PRESSURE));
+        ret.add_prefixable_unit("bar", Unit::with_factor(PRESSURE, N::from(1e5).unwrap()));
+        ret.add_prefixable_unit("Wh", Unit::with_factor(ENERGY, N::from(3600).unwrap()));
+
+        let five_ninths = N::from(5).unwrap()/N::from(9).unwrap();
+        ret.add_unit("°C", Unit::with_offset(TEMPERATURE, N::one(), N::from(273.15).unwrap()));
+        ret.add_unit("°F", Unit::with_offset(TEMPERATURE, five_ninths, N::from(459.67).unwrap()*five_ninths));
+        ret.add_unit("Δ°C", Unit::new(TEMPERATURE));
+        ret.add_unit("Δ°F", Unit::with_factor(TEMPERATURE, five_ninths));
+
+        let ten = N::from(10).unwrap();
+        let bel = LogScale::new(ten, N::one(), false);
+        let decibel = LogScale::new(ten, ten, false);
+        ret.add_unit("B", Unit::with_log(NUL, N::one(), bel));
+        ret.add_unit("dB", Unit::with_log(NUL, N::one(), decibel));
+        ret.add_unit("Np", Unit::with_log(NUL, N::one(), LogScale::new(N::one().exp(), N::from(0.5).unwrap(), false)));
+        ret.add_unit("dBW", Unit::with_log(POWER, N::one(), decibel));
+        ret.add_unit("dBm", Unit::with_log(POWER, N::from(1e-3).unwrap(), decibel));
+        ret.add_unit("dBV", Unit::with_log(VOLTAGE, N::one(), LogScale{field: true, ..decibel}));
+        // Negative decimal logarithms of concentrations in mol/L
+        let p = Unit::with_log(CONCENTRATION, N::from(1e3).unwrap(), LogScale::new(ten, -N::one(), false));
+        ret.add_unit("pH", p);
+        ret.add_unit("pOH", p);
+        ret.add_unit("pKa", p);
+
+        ret.add_angles(NUL);
+
+        for &(symbol, singular, plural) in &[
+            ("m", "metre", "metres"), ("s", "second", "seconds"), ("kg", "kilogram", "kilograms"),
+            ("A", "ampere", "amperes"), ("K", "kelvin", "kelvins"), ("mol", "mole", "moles"),
+            ("cd", "candela", "candelas"), ("J", "joule", "joules"), ("min", "minute", "minutes"),
+            ("h", "hour", "hours"), ("g", "gram", "grams"), ("Hz", "hertz", "hertz"),
+            ("L", "litre", "litres"), ("M", "molar", "molar"), ("N", "newton", "newtons"),
+            ("W", "watt", "watts"), ("V", "volt", "volts"), ("Ω", "ohm", "ohms"),
+            ("C", "coulomb", "coulombs"), ("F", "farad", "farads"), ("Pa", "pascal", "pascals"),
+            ("bar", "bar", "bars"), ("Wh", "watt-hour", "watt-hours"),
+            ("°C", "degree Celsius", "degrees Celsius"), ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+            ("B", "bel", "bels"), ("dB", "decibel", "decibels"), ("Np", "neper", "nepers"),
+            ("rad", "radian", "radians"), ("sr", "steradian", "steradians"), ("deg", "degree", "degrees"),
+            ("arcmin", "arcminute", "arcminutes"), ("arcsec", "arcsecond", "arcseconds"),
+            ("grad", "gradian", "gradians"), ("turn", "turn", "turns"),
+        ] {
+            ret.add_names(symbol, singular, plural);
+        }
+        for &(alias, symbol) in &[
+            ("meter", "m"), ("meters", "m"), ("sec", "s"), ("secs", "s"), ("amp", "A"), ("amps", "A"),
+            ("mins", "min"), ("hr", "h"), ("hrs", "h"), ("gramme", "g"), ("grammes", "g"),
+            ("liter", "L"), ("liters", "L"), ("celsius", "°C"), ("degC", "°C"),
+            ("fahrenheit", "°F"), ("degF", "°F"), ("°", "deg"), ("gon", "grad"), ("rev", "turn"),
+        ] {
+            ret.add_alias(alias, symbol);
+        }
+
+        ret
+    }
+    /// Creates a system with SI units where angles have their own dimension
+    ///
+    /// Radians are then a base unit, and steradians are square radians,
+    /// so rad/s is an angular velocity and not the same as Hz
+    pub fn si_with_angles() -> Self {
+        let mut ret = Self::si();
+        ret.base.angle = Some(Cow::Borrowed("rad"));
+        ret.add_angles(ANGLE);
+        ret
+    }
+    /// The dimension of angles, which is `ANGLE` if the system keeps track of them and else `NUL`
+    pub fn angle(&self) -> Dimension {
+        if self.base.angle.is_some() {ANGLE} else {NUL}
+    }
+    /// Adds the units of angle and solid angle, where `angle` is the dimension of a radian
+    fn add_angles(&mut self, angle: Dimension) {
+        let rad = Unit::new(angle);
+        let deg = Unit::with_factor(angle, N::from(std::f64::consts::PI / 180.).unwrap());
+        self.add_unit("rad", rad);
+        self.add_prefixable_unit("sr", rad + rad);
+        self.add_unit("deg", deg);
+        self.add_unit("arcmin", Unit::with_factor(angle, deg.factor / N::from(60).unwrap()));
+        self.add_unit("arcsec", Unit::with_factor(angle, deg.factor / N::from(3600).unwrap()));
+        self.add_unit("grad", Unit::with_factor(angle, N::from(std::f64::consts::PI / 200.).unwrap()));
+        self.add_unit("turn", Unit::with_factor(angle, N::from(std::f64::consts::TAU).unwrap()));
+    }
+    /// Creates a system with SI units that can also read the imperial and US customary units
+    ///
+    /// Values are still displayed in SI units. Gallons, pints, fluid ounces and tons differ
+    /// between the two, so they are only read with `UK` or `US` in front, like `USgal`
+    pub fn si_with_customary_units() -> Self {
+        let mut ret = Self::si().with_customary_units();
+        ret.add_customary_names();
+        ret
+    }
+    /// Creates a system with SI units and the imperial units of the UK
+    ///
+    /// Gallons, pints, fluid ounces and tons are the imperial ones and
+    /// values are displayed in imperial units where possible
+    pub fn imperial() -> Self {
+        let mut ret = Self::si().with_customary_units();
+
+        let (gal, pt, floz, ton) = (ret["UKgal"], ret["UKpt"], ret["UKfloz"], ret["longton"]);
+        ret.add_unit("gal", gal);
+        ret.add_unit("pt", pt);
+        ret.add_unit("floz", floz);
+        ret.add_unit("ton", ton);
+        ret.add_customary_names();
+        ret.prefer_customary();
+
+        ret
+    }
+    /// Creates a system with SI units and the US customary units
+    ///
+    /// Gallons, pints, fluid ounces and tons are the US ones and
+    /// values are displayed in US customary units where possible
+    pub fn us_customary() -> Self {
+        let mut ret = Self::si().with_customary_units();
+
+        let (gal, pt, floz, ton) = (ret["USgal"], ret["USpt"], ret["USfloz"], ret["shortton"]);
+        ret.add_unit("gal", gal);
+        ret.add_unit("pt", pt);
+        ret.add_unit("floz", floz);
+        ret.add_unit("ton", ton);
+        ret.add_customary_names();
+        ret.prefer_customary();
 
         ret
     }
+    /// Adds the units shared by the imperial and US customary systems,
+    /// with the units that differ between them prefixed by `UK` or `US`
+    ///
+    /// All factors are the exact definitions in terms of SI units
+    fn with_customary_units(mut self) -> Self {
+        let n = |f: f64| N::from(f).unwrap();
+        let inch = n(0.0254);
+        let pound = n(0.45359237);
+        let pound_force = pound * n(9.80665);
+
+        self.add_unit("in", Unit::with_factor(LENGTH, inch));
+        self.add_unit("ft", Unit::with_factor(LENGTH, n(0.3048)));
+        self.add_unit("yd", Unit::with_factor(LENGTH, n(0.9144)));
+        self.add_unit("mi", Unit::with_factor(LENGTH, n(1609.344)));
+
+        self.add_unit("oz", Unit::with_factor(MASS, pound / n(16.)));
+        self.add_unit("lb", Unit::with_factor(MASS, pound));
+        self.add_unit("st", Unit::with_factor(MASS, pound * n(14.)));
+        self.add_unit("shortton", Unit::with_factor(MASS, pound * n(2000.)));
+        self.add_unit("longton", Unit::with_factor(MASS, pound * n(2240.)));
+
+        self.add_unit("USfloz", Unit::with_factor(VOLUME, n(29.5735295625e-6)));
+        self.add_unit("USpt", Unit::with_factor(VOLUME, n(473.176473e-6)));
+        self.add_unit("USgal", Unit::with_factor(VOLUME, n(3.785411784e-3)));
+        self.add_unit("UKfloz", Unit::with_factor(VOLUME, n(28.4130625e-6)));
+        self.add_unit("UKpt", Unit::with_factor(VOLUME, n(568.26125e-6)));
+        self.add_unit("UKgal", Unit::with_factor(VOLUME, n(4.54609e-3)));
+
+        self.add_unit("lbf", Unit::with_factor(FORCE, pound_force));
+        self.add_unit("psi", Unit::with_factor(PRESSURE, pound_force / (inch * inch)));
+        self.add_unit("BTU", Unit::with_factor(ENERGY, n(1055.05585262)));
+        self.add_unit("hp", Unit::with_factor(POWER, n(550.) * n(0.3048) * pound_force));
+
+        self
+    }
+    /// Adds the long names of the imperial and US customary units
+    fn add_customary_names(&mut self) {
+        for &(symbol, singular, plural) in &[
+            ("in", "inch", "inches"), ("ft", "foot", "feet"), ("yd", "yard", "yards"), ("mi", "mile", "miles"),
+            ("oz", "ounce", "ounces"), ("lb", "pound", "pounds"), ("st", "stone", "stone"),
+            ("shortton", "short ton", "short tons"), ("longton", "long ton", "long tons"),
+            ("gal", "gallon", "gallons"), ("pt", "pint", "pints"), ("floz", "fluid ounce", "fluid ounces"),
+            ("ton", "ton", "tons"), ("lbf", "pound-force", "pounds-force"),
+            ("psi", "pound per square inch", "pounds per square inch"),
+            ("BTU", "British thermal unit", "British thermal units"), ("hp", "horsepower", "horsepower"),
+        ] {
+            self.add_names(symbol, singular, plural);
+        }
+        self.add_alias("lbs", "lb");
+    }
+    /// Prefers displaying values in imperial or US customary units
+    fn prefer_customary(&mut self) {
+        for unit in &["mi", "ft", "in", "lb", "oz", "gal", "pt", "floz", "°F", "mi/h", "lbf", "psi", "BTU", "hp"] {
+            self.prefer(unit).unwrap();
+        }
+    }
     /// Add a unit to the system
-    pub fn add_unit(&mut self, name: &'static str, unit: Unit<N>) -> Option<Unit<N>> {
-        self.units.insert(name, unit)
+    ///
+    /// `name` can be a `&'static str` or a `String` of a unit defined at runtime
+    pub fn add_unit<S: Into<Cow<'static, str>>>(&mut self, name: S, unit: Unit<N>) -> Option<Unit<N>> {
+        self.units.insert(name.into(), unit)
+    }
+    /// Add a unit to the system that can be combined with the SI prefixes
+    pub fn add_prefixable_unit<S: Into<Cow<'static, str>>>(&mut self, name: S, unit: Unit<N>) -> Option<Unit<N>> {
+        let name = name.into();
+        self.prefixable.insert(name.clone());
+        self.add_unit(name, unit)
+    }
+    /// Removes a unit from the system, along with the preferences to display values in it
+    ///
+    /// Returns the unit if there was one by that name.
+    /// Base units can't be removed, so this does nothing and returns `None` for them
+    pub fn remove_unit(&mut self, name: &str) -> Option<Unit<N>> {
+        if self.base.contains(name) {
+            return None
+        }
+        let unit = self.units.remove(name)?;
+        self.prefixable.remove(name);
+        self.names.remove(name);
+        self.aliases.retain(|_, symbol| symbol != name);
+        if let Some(preferred) = self.preferred.get_mut(&unit.dimension) {
+            preferred.retain(|(n, _)| n != name);
+        }
+        Some(unit)
+    }
+    /// Gives the unit `from` the name `to`, keeping whether it's prefixable, preferred or a base unit
+    ///
+    /// Returns the unit if there was one by the name `from`.
+    /// Does nothing and returns `None` if there already is a unit `to`
+    pub fn rename_unit<S: Into<Cow<'static, str>>>(&mut self, from: &str, to: S) -> Option<Unit<N>> {
+        let to = to.into();
+        if self.units.contains_key(&to) {
+            return None
+        }
+        let unit = self.units.remove(from)?;
+        for base in self.base.names_mut().filter(|base| *base == from) {
+            *base = to.clone();
+        }
+        if self.prefixable.remove(from) {
+            self.prefixable.insert(to.clone());
+        }
+        if let Some(names) = self.names.remove(from) {
+            self.names.insert(to.clone(), names);
+        }
+        for symbol in self.aliases.values_mut().filter(|symbol| *symbol == from) {
+            *symbol = to.clone();
+        }
+        if let Some(preferred) = self.preferred.get_mut(&unit.dimension) {
+            for (name, _) in preferred.iter_mut().filter(|(n, _)| n == from) {
+                *name = to.clone().into_owned();
+            }
+        }
+        self.units.insert(to, unit);
+        Some(unit)
+    }
+    /// Gives the unit `symbol` long names, which it can also be read by
+    ///
+    /// Names of several words like `degree Celsius` or `watt-hour` are read as a whole,
+    /// before the words in them. Does nothing and returns false if there is no unit `symbol`
+    pub fn add_names<S, P>(&mut self, symbol: &str, singular: S, plural: P) -> bool
+    where S: Into<Cow<'static, str>>, P: Into<Cow<'static, str>> {
+        let symbol = match self.units.get_key_value(symbol) {
+            Some((symbol, _)) => symbol.clone(),
+            None => return false,
+        };
+        let names = UnitNames {singular: singular.into(), plural: plural.into()};
+        for name in &[&names.singular, &names.plural] {
+            self.add_alias((*name).clone(), &symbol);
+        }
+        self.names.insert(symbol, names);
+        true
+    }
+    /// Lets the unit `symbol` be read by `alias` as well
+    ///
+    /// Aliases never shadow units. Does nothing and returns false if there is no unit `symbol`
+    pub fn add_alias<S: Into<Cow<'static, str>>>(&mut self, alias: S, symbol: &str) -> bool {
+        let symbol = match self.units.get_key_value(symbol) {
+            Some((symbol, _)) => symbol.clone(),
+            None => return false,
+        };
+        let alias = alias.into();
+        if alias != symbol {
+            self.aliases.insert(alias, symbol);
+        }
+        true
+    }
+    /// Returns the long name of the unit `symbol` for one or, if `plural`, any other number of it
+    pub fn long_name(&self, symbol: &str, plural: bool) -> Option<&str> {
+        self.names.get(symbol).map(|names| if plural {&*names.plural} else {&*names.singular})
+    }
+    /// Adds a unit to the end of the list of units to display values of its dimension in
+    ///
+    /// `unit` may be composite, e.g. `"km/h"`, and is written as given
+    pub fn prefer(&mut self, unit: &str) -> Result<(), ParseError> {
+        let u = self.unit_from_str(unit)?;
+        self.preferred.entry(u.dimension).or_default().push((unit.to_owned(), u));
+        Ok(())
     }
     /// Returns the unit with the given name if it exists
     ///
-    /// This can only take units that aren't composite (i.e m, C, s, etc., but not m³, m/s or s^-1)
+    /// This can only take units that aren't composite (i.e m, C, s, etc., but not m³, m/s or s^-1).
+    /// Units can also be read by their aliases and long names, like `meter` or `metres`.
+    /// If there is no unit by that name, it is tried as an SI prefix on a prefixable unit
+    /// (e.g. km or µs), or the name of one on a long name (e.g. kilometres or microseconds)
     pub fn get_unit(&self, name: &str) -> Option<Unit<N>> {
-        self.units.get(name).cloned()
+        let alias = |name: &str| self.aliases.get(name).map(|symbol| &**symbol);
+        // Only units in `units` are looked up, so a stray name in `prefixable` is ignored
+        let prefixed = |(prefix, rest): (Prefix, &str)| {
+            self.units.get(rest)
+                .filter(|_| self.prefixable.contains(rest))
+                .map(|unit| Unit::with_factor(unit.dimension, unit.factor * prefix.factor()))
+        };
+        self.units.get(name)
+            .or_else(|| self.constants.get(name))
+            .or_else(|| alias(name).and_then(|symbol| self.units.get(symbol)))
+            .cloned()
+            .or_else(|| split_prefix(name).filter_map(prefixed).next())
+            .or_else(|| {
+                split_prefix_name(name)
+                    .filter_map(|(prefix, rest)| alias(rest).map(|symbol| (prefix, symbol)))
+                    .filter_map(prefixed)
+                    .next()
+            })
+    }
+    /// Adds the CODATA 2018 constants, so they can be read by their symbols like `c` or `k_B`
+    ///
+    /// Units are read before constants, so `h` stays the hour
+    /// and Planck's constant is read as `h_P` or `ℎ` (see `constants::ALIASES`)
+    pub fn add_constants(&mut self) {
+        for constant in constants::CODATA_2018 {
+            let value = constant.value();
+            self.add_constant(constant.symbol, Unit::with_factor(value.1.dimension, value.0));
+        }
+        for &(alias, symbol) in constants::ALIASES {
+            let unit = self.constants[symbol];
+            self.add_constant(alias, unit);
+        }
+    }
+    /// Adds a constant that can be read like a unit, but is never used for displaying values
+    ///
+    /// `name` can be a `&'static str` or a `String` of a constant defined at runtime
+    pub fn add_constant<S: Into<Cow<'static, str>>>(&mut self, name: S, unit: Unit<N>) -> Option<Unit<N>> {
+        self.constants.insert(name.into(), unit)
+    }
+    /// Returns names of units similar to `name`, closest first
+    ///
+    /// Used to suggest what was meant by a name that isn't a unit.
+    /// Names are ranked by their edit distance, then by their `SuggestionRank`
+    pub fn suggestions(&self, name: &str) -> Vec<String> {
+        let max_distance = |name: &str| if name.chars().count() > 3 {2} else {1};
+        let base = &self.base;
+        let base_units = [
+            &base.length, &base.time, &base.mass, &base.current, &base.temperature,
+            &base.substance_amount, &base.luminous_intensity,
+        ];
+
+        let mut similar: Vec<_> = self.units.keys().chain(self.aliases.keys()).chain(self.constants.keys())
+            .map(|unit| &**unit)
+            .map(|unit| (edit_distance(name, unit), SuggestionRank::Unprefixed, unit.to_owned()))
+            .filter(|&(d, _, _)| d <= max_distance(name))
+            .collect();
+        for (prefix, rest) in split_prefix(name) {
+            similar.extend(self.prefixable.iter()
+                .map(|unit| {
+                    let rank = if base_units.contains(&unit) {SuggestionRank::PrefixedBase} else {SuggestionRank::Prefixed};
+                    (edit_distance(rest, unit), rank, format!("{}{}", prefix.symbol, unit))
+                })
+                .filter(|&(d, _, _)| d <= max_distance(rest)));
+        }
+        similar.sort();
+
+        let mut seen = HashSet::new();
+        similar.into_iter()
+            .map(|(_, _, unit)| unit)
+            .filter(|unit| seen.insert(unit.clone()))
+            .take(5)
+            .collect()
     }
     /// Returns a composite unit from a string
     ///
     /// E.g `"m²"` should return a `Unit` for m, if it exists, squared
-    pub fn unit_from_str(&self, s: &str) -> Option<Unit<N>> {
+    pub fn unit_from_str(&self, s: &str) -> Result<Unit<N>, ParseError> {
         unit_from_str(self, s)
     }
     /// Returns a value with the given composite unit from a string if the units exist
-    pub fn val(&self, val: N, unit_str: &str) -> Option<Value<N>> {
-        Some(Value(val, self.unit_from_str(unit_str)?))
+    pub fn val(&self, val: N, unit_str: &str) -> Result<Value<N>, ParseError> {
+        Ok(Value(val, self.unit_from_str(unit_str)?))
     }
     /// Returns a value from a string
-    pub fn val_s(&self, value: &str) -> Option<Value<N>>
+    pub fn val_s(&self, value: &str) -> Result<Value<N>, ParseError>
     where N: std::str::FromStr {
         value_from_str(self, value)
     }
+    /// Returns a value with an uncertainty from a string like `9.81 ± 0.02 m/s²` or `9.81(2) m/s²`
+    pub fn uncertain_s(&self, value: &str) -> Result<Uncertain<N>, ParseError>
+    where N: std::str::FromStr {
+        uncertain_from_str(self, value)
+    }
     /// Casts a `Value` to the one given
     ///
     /// `unit` may be composite
     /// ## Panics
-    /// Panicks if `val` doesn't have the same dimension as the unit
+    /// Panicks if `val` doesn't have the same dimension as the unit or if the unit can't be read
     pub fn as_(&self, val: Value<N>, unit: &str) -> Value<N> {
-        self.cast(val, &self.unit_from_str(unit).unwrap())
+        self.try_as(val, unit).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Casts a `Value` to the one given
+    ///
+    /// `unit` may be composite.
+    /// Fails if `val` doesn't have the same dimension as the unit or if the unit can't be read
+    pub fn try_as(&self, val: Value<N>, unit: &str) -> Result<Value<N>, UnitError> {
+        self.try_cast(val, &self.unit_from_str(unit)?)
     }
     /// Casts a `Value` to the one given
     ///
     /// ## Panics
     /// Panicks if `val` doesn't have the same dimension as `unit`
     pub fn cast(&self, val: Value<N>, unit: &Unit<N>) -> Value<N> {
+        self.try_cast(val, unit).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Casts a `Value` to the one given
+    ///
+    /// Fails if `val` doesn't have the same dimension as `unit`
+    pub fn try_cast(&self, val: Value<N>, unit: &Unit<N>) -> Result<Value<N>, UnitError> {
         if val.1.dimension == unit.dimension {
-            Value(val.0 * (val.1.factor/unit.factor), *unit)
+            Ok(Value(val.1.convert(val.0, unit), *unit))
         } else {
-            panic!("Tried to cast from {:#} to {:#}", val.1.dimension, unit.dimension);
+            Err(UnitError::DimensionMismatch(val.1.dimension, unit.dimension))
         }
     }
+    /// Returns the simplest way to write a dimension as a product of named units of this system
+    ///
+    /// Only units with a factor of one are used
+    pub fn simplify(&self, dim: Dimension) -> Vec<(&str, Exponent)> {
+        display::simplify(self, dim)
+    }
     /// Returns a `UnitDisplay` used to display a value
     pub fn display<'a>(&'a self, val: &'a Value<N>) -> UnitDisplay<'a, N> {
         display::make_display(self, val)
     }
+    /// Returns a `UnitDisplay` used to display a value with its uncertainty
+    pub fn display_uncertain<'a>(&'a self, val: &'a Uncertain<N>) -> UnitDisplay<'a, N> {
+        self.display(&val.value).with_uncertainty(val.uncertainty())
+    }
+}
+
+/// How likely a suggested name is what was meant, most likely first
+///
+/// Used to order suggestions at the same edit distance, as a misspelt `kmh`
+/// is more likely to be `km` with a typo than the prefixed derived unit `kWh`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SuggestionRank {
+    /// A name as it is in the system, without a prefix
+    Unprefixed,
+    /// A prefixed base unit, like `km`
+    PrefixedBase,
+    /// Any other prefixed unit, like `kWh`
+    Prefixed,
+}
+
+/// The number of single character insertions, deletions and substitutions to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..b.len()+1).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i+1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + if ca == cb {0} else {1};
+            diagonal = row[j+1];
+            row[j+1] = substitution.min(row[j]+1).min(diagonal+1);
+        }
+    }
+    row[b.len()]
 }
 
 use std::ops::Index;
@@ -154,12 +613,64 @@ impl<'a, N: Float> Index<&'a str> for UnitSystem<N> {
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+/// The scale of a logarithmic unit like the decibel
+///
+/// A level `x` stands for `base^(x / multiplier)` times the reference of the unit for power quantities.
+/// Field quantities (like voltage), whose squares are proportional to power,
+/// are `base^(x / (2·multiplier))` times the reference, so a decibel is the same ratio of powers
+/// whether it's measured in dBW or dBV.
+pub struct LogScale<N: Float> {
+    /// The base of the logarithm
+    pub base: N,
+    /// The number of units in a factor of `base` in power
+    pub multiplier: N,
+    /// Whether the unit measures a field quantity rather than a power quantity
+    pub field: bool,
+}
+
+impl<N: Float> LogScale<N> {
+    /// Creates a new logarithmic scale
+    pub fn new(base: N, multiplier: N, field: bool) -> Self {
+        LogScale {
+            base,
+            multiplier,
+            field,
+        }
+    }
+    /// The number of units in a factor of `base` of the quantity itself
+    fn exponent_multiplier(&self) -> N {
+        if self.field {
+            self.multiplier + self.multiplier
+        } else {
+            self.multiplier
+        }
+    }
+    /// The level of a ratio between two powers on this scale
+    pub fn level(&self, power_ratio: N) -> N {
+        self.multiplier * power_ratio.ln() / self.base.ln()
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 /// A unit
+///
+/// A unit with a non-zero offset (like °C) is affine and measures absolute values,
+/// whereas a unit without one measures differences as well.
+/// A logarithmic unit (like dBm) measures the level of a quantity relative to its factor,
+/// and a dimensionless one (like dB) measures gains.
 pub struct Unit<N: Float> {
     /// Dimensions of this unit
     pub dimension: Dimension,
     /// The number to multiply it by to get it in terms of base units
-    pub factor: N
+    ///
+    /// For logarithmic units, this is the reference quantity in base units
+    pub factor: N,
+    /// The number to add after multiplying by the factor to get it in terms of base units
+    pub offset: N,
+    /// The scale of a logarithmic unit
+    pub log: Option<LogScale<N>>,
 }
 
 impl<N: Float> PartialOrd for Unit<N> {
@@ -177,30 +688,123 @@ impl<N: Float> Unit<N> {
     ///
     /// Will assume to be made of base units
     pub fn new(dimension: Dimension) -> Self {
-        Unit {
-            factor: N::one(),
-            dimension
-        }
+        Self::with_factor(dimension, N::one())
     }
     /// Creates a new unit with the given dimension and factor
     ///
     /// Factor is the number to multiply it by to get it in terms of base units
     pub fn with_factor(dimension: Dimension, factor: N) -> Self {
+        Self::with_offset(dimension, factor, N::zero())
+    }
+    /// Creates a new affine unit with the given dimension, factor and offset
+    ///
+    /// A value `x` in this unit is `x * factor + offset` in terms of base units
+    pub fn with_offset(dimension: Dimension, factor: N, offset: N) -> Self {
         Unit {
             factor,
-            dimension
+            dimension,
+            offset,
+            log: None,
+        }
+    }
+    /// Creates a new logarithmic unit measuring levels relative to `reference` in base units
+    pub fn with_log(dimension: Dimension, reference: N, log: LogScale<N>) -> Self {
+        Unit {
+            factor: reference,
+            dimension,
+            offset: N::zero(),
+            log: Some(log),
+        }
+    }
+    /// Returns true if this unit has an offset and therefore measures absolute values
+    pub fn is_affine(&self) -> bool {
+        self.offset != N::zero()
+    }
+    /// Returns true if this unit is logarithmic and measures levels
+    pub fn is_logarithmic(&self) -> bool {
+        self.log.is_some()
+    }
+    /// Returns true if this is a dimensionless logarithmic unit measuring gains, like dB
+    pub fn is_gain(&self) -> bool {
+        self.is_logarithmic() && self.dimension == NUL && self.factor == N::one()
+    }
+    /// Returns the unit measuring differences on the same scale as this one
+    ///
+    /// E.g. the difference unit of °C is K
+    pub fn difference(self) -> Self {
+        Self::with_factor(self.dimension, self.factor)
+    }
+    /// Converts a number in this unit to base units
+    pub fn to_base(&self, n: N) -> N {
+        match self.log {
+            Some(log) => self.factor * log.base.powf(n / log.exponent_multiplier()),
+            None => n * self.factor + self.offset,
+        }
+    }
+    /// Converts a number in base units to this unit
+    pub fn from_base(&self, n: N) -> N {
+        match self.log {
+            Some(log) => log.exponent_multiplier() * (n / self.factor).ln() / log.base.ln(),
+            None => (n - self.offset) / self.factor,
+        }
+    }
+    /// The rate at which the number in base units changes with the number `n` in this unit
+    ///
+    /// Used for converting small differences like uncertainties
+    pub fn base_slope(&self, n: N) -> N {
+        match self.log {
+            Some(log) => self.to_base(n) * log.base.ln() / log.exponent_multiplier(),
+            None => self.factor,
+        }
+    }
+    /// Converts a number in this unit to the given unit of the same dimension
+    pub fn convert(&self, n: N, to: &Self) -> N {
+        if self == to {
+            n
+        } else {
+            to.from_base(self.to_base(n))
         }
     }
+    /// Multiplies two units like `+` does, returning `None` if an exponent of the dimension overflows
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.dimension.checked_add(rhs.dimension)?;
+        Some(self + rhs)
+    }
+    /// Divides two units like `-` does, returning `None` if an exponent of the dimension overflows
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.dimension.checked_sub(rhs.dimension)?;
+        Some(self - rhs)
+    }
+    /// Raises the unit to the power `rhs` like `*` does, returning `None` if an exponent of the dimension overflows
+    pub fn checked_mul(self, rhs: Exponent) -> Option<Self> {
+        self.dimension.checked_mul(rhs)?;
+        Some(self * rhs)
+    }
+    /// Whether this is the dimensionless unit with no factor, which keeps
+    /// the offset and logarithmic scale of the unit it's combined with
+    fn is_identity(&self) -> bool {
+        self.dimension == NUL && self.factor == N::one() && !self.is_affine() && !self.is_logarithmic()
+    }
 }
 
 impl<N: Float> Add for Unit<N> {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
-        let Unit{factor, dimension} = self;
-        let Unit{factor:f, dimension:d} = rhs;
+        // Only a lone affine unit keeps its offset; combined it measures differences
+        let (offset, log) = if rhs.is_identity() {
+            (self.offset, self.log)
+        } else if self.is_identity() {
+            (rhs.offset, rhs.log)
+        } else {
+            (N::zero(), None)
+        };
+        let Unit{factor, dimension, ..} = self;
+        let Unit{factor:f, dimension:d, ..} = rhs;
         Unit{
             factor: factor*f,
-            dimension: dimension+d
+            dimension: dimension+d,
+            offset,
+            log,
         }
     }
 }
@@ -208,97 +812,205 @@ impl<N: Float> Add for Unit<N> {
 impl<N: Float> Sub for Unit<N> {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
-        let Unit{factor, dimension} = self;
-        let Unit{factor:f, dimension:d} = rhs;
+        let (offset, log) = if rhs.is_identity() {(self.offset, self.log)} else {(N::zero(), None)};
+        let Unit{factor, dimension, ..} = self;
+        let Unit{factor:f, dimension:d, ..} = rhs;
         Unit{
             factor: factor/f,
-            dimension: dimension-d
+            dimension: dimension-d,
+            offset,
+            log,
         }
     }
 }
 
-impl<N: Float> Mul<i16> for Unit<N> {
+impl<N: Float> Mul<Exponent> for Unit<N> {
     type Output = Self;
-    fn mul(self, rhs: i16) -> Self::Output {
-        let Unit{factor, dimension} = self;
+    fn mul(self, rhs: Exponent) -> Self::Output {
+        let Unit{factor, dimension, offset, log} = self;
         Unit{
-            factor: factor.powi(rhs as i32),
-            dimension: rhs*dimension
+            factor: rhs.pow(factor),
+            dimension: dimension*rhs,
+            offset: if rhs == Exponent::ONE {offset} else {N::zero()},
+            log: if rhs == Exponent::ONE {log} else {None},
         }
     }
 }
 
+impl<N: Float> Mul<i16> for Unit<N> {
+    type Output = Self;
+    fn mul(self, rhs: i16) -> Self::Output {
+        self * Exponent::integer(rhs)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 /// A floating value with an associated unit.
 ///
 /// Will only allow addition and subtraction with values of the same dimensions.
 /// Unit will change accordingly when performing mathematical operations.
+///
+/// Values in an affine unit (like °C) are absolute: two of them can't be added,
+/// and subtracting them gives a difference in the matching difference unit.
+/// Multiplying or dividing them uses their absolute value in base units.
+///
+/// Values in a logarithmic unit (like dBm) are levels: adding a gain (like dB) to them
+/// multiplies by its ratio, and subtracting two levels gives the gain between them.
+/// Multiplying or dividing them uses their linear value in base units.
 pub struct Value<N: Float>(pub N, pub Unit<N>);
 
+impl<N: Float> Value<N> {
+    /// Converts a value in an affine or logarithmic unit to the same value in base units
+    ///
+    /// Values in other units are returned as they are
+    pub fn absolute(self) -> Self {
+        if self.1.is_affine() || self.1.is_logarithmic() {
+            Value(self.1.to_base(self.0), Unit::new(self.1.dimension))
+        } else {
+            self
+        }
+    }
+    /// Adds two values, failing if they don't have the same dimension
+    /// or if they are both absolute
+    ///
+    /// The result is in the unit of `self`, unless only `rhs` is absolute
+    pub fn checked_add(self, rhs: Self) -> Result<Self, UnitError> {
+        if self.1.is_logarithmic() || rhs.1.is_logarithmic() {
+            return self.add_levels(rhs, false)
+        }
+        if self.1.dimension != rhs.1.dimension {
+            return Err(UnitError::DimensionMismatch(self.1.dimension, rhs.1.dimension));
+        }
+        match (self.1.is_affine(), rhs.1.is_affine()) {
+            (true, true) => return Err(UnitError::AbsoluteAddition),
+            (false, true) => return rhs.checked_add(self),
+            _ => (),
+        }
+        let convert = if self.1.factor != rhs.1.factor {
+            rhs.1.factor/self.1.factor
+        } else {
+            N::one()
+        };
+        Ok(Value(self.0+convert*rhs.0, self.1))
+    }
+    /// Subtracts `rhs` from this value, failing if they don't have the same dimension
+    /// or if `rhs` is absolute but `self` isn't
+    ///
+    /// The result is in the unit of `self`, or its difference unit if both are absolute
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, UnitError> {
+        if self.1.is_logarithmic() || rhs.1.is_logarithmic() {
+            return self.add_levels(rhs, true)
+        }
+        if self.1.dimension != rhs.1.dimension {
+            return Err(UnitError::DimensionMismatch(self.1.dimension, rhs.1.dimension));
+        }
+        match (self.1.is_affine(), rhs.1.is_affine()) {
+            (true, true) => return Ok(Value(self.0-rhs.1.convert(rhs.0, &self.1), self.1.difference())),
+            (false, true) => return Err(UnitError::AbsoluteAddition),
+            _ => (),
+        }
+        let convert = if self.1.factor != rhs.1.factor {
+            rhs.1.factor/self.1.factor
+        } else {
+            N::one()
+        };
+        Ok(Value(self.0-convert*rhs.0, self.1))
+    }
+    /// Multiplies two values, failing if an exponent of the dimension overflows
+    ///
+    /// Values in affine or logarithmic units are made absolute first
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, UnitError> {
+        let (lhs, rhs) = (self.absolute(), rhs.absolute());
+        let unit = lhs.1.checked_add(rhs.1).ok_or(UnitError::ExponentOverflow)?;
+        Ok(Value(lhs.0*rhs.0, unit))
+    }
+    /// Divides this value by `rhs`, failing if an exponent of the dimension overflows
+    ///
+    /// Values in affine or logarithmic units are made absolute first
+    pub fn checked_div(self, rhs: Self) -> Result<Self, UnitError> {
+        let (lhs, rhs) = (self.absolute(), rhs.absolute());
+        let unit = lhs.1.checked_sub(rhs.1).ok_or(UnitError::ExponentOverflow)?;
+        Ok(Value(lhs.0/rhs.0, unit))
+    }
+    /// Adds or subtracts values where at least one is in a logarithmic unit
+    ///
+    /// A gain can be added to or subtracted from any level, giving a level in the same unit.
+    /// Subtracting two levels of the same dimension gives the gain between them
+    fn add_levels(self, rhs: Self, subtract: bool) -> Result<Self, UnitError> {
+        let log = match self.1.log {
+            Some(log) if rhs.1.is_logarithmic() => log,
+            _ => return Err(UnitError::LogarithmicAddition),
+        };
+        if rhs.1.is_gain() {
+            let level = match rhs.1.log {
+                // Avoid rounding errors when the gain is on the same scale
+                Some(l) if l.base == log.base && l.multiplier == log.multiplier => rhs.0,
+                _ => log.level(rhs.1.to_base(rhs.0)),
+            };
+            return Ok(Value(if subtract {self.0 - level} else {self.0 + level}, self.1))
+        }
+        if !subtract {
+            return if self.1.is_gain() {rhs.add_levels(self, false)} else {Err(UnitError::LogarithmicAddition)}
+        }
+        if self.1.dimension != rhs.1.dimension {
+            return Err(UnitError::DimensionMismatch(self.1.dimension, rhs.1.dimension));
+        }
+        let gain = Unit::with_log(NUL, N::one(), LogScale{field: false, ..log});
+        Ok(Value(self.0 - rhs.1.convert(rhs.0, &self.1), gain))
+    }
+}
+
 impl<N: Float> PartialOrd for Value<N> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         if self.1.dimension == other.1.dimension {
-            let factor = self.1.factor/other.1.factor;
-            self.0.partial_cmp(&(self.0 * factor))
+            self.1.to_base(self.0).partial_cmp(&other.1.to_base(other.0))
         } else {
             None
         }
     }
 }
 
-impl<N: Float + Debug> Add for Value<N> {
+impl<N: Float> Add for Value<N> {
     type Output = Self;
     fn add(self, rhs: Self) -> Self {
-        assert_eq!(self.1.dimension, rhs.1.dimension);
-        let convert;
-        if self.1.factor != rhs.1.factor {
-            convert = rhs.1.factor/self.1.factor;
-        } else {
-            convert = N::one();
-        }
-        Value(self.0+convert*rhs.0, self.1)
+        self.checked_add(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
-impl<N: Float + Debug> Sub for Value<N> {
+impl<N: Float> Sub for Value<N> {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self {
-        assert_eq!(self.1.dimension, rhs.1.dimension);
-        let convert;
-        if self.1.factor != rhs.1.factor {
-            convert = rhs.1.factor/self.1.factor;
-        } else {
-            convert = N::one();
-        }
-        Value(self.0-convert*rhs.0, self.1)
+        self.checked_sub(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl<N: Float> Mul for Value<N> {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self {
-        Value(self.0*rhs.0, self.1+rhs.1)
+        self.checked_mul(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl<N: Float> Div for Value<N> {
     type Output = Self;
     fn div(self, rhs: Self) -> Self {
-        Value(self.0/rhs.0, self.1-rhs.1)
+        self.checked_div(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
 impl<N: Float> Mul<N> for Value<N> {
     type Output = Self;
     fn mul(self, rhs: N) -> Self {
-        Value(self.0*rhs, self.1)
+        let lhs = self.absolute();
+        Value(lhs.0*rhs, lhs.1)
     }
 }
 
 impl<N: Float> Div<N> for Value<N> {
     type Output = Self;
     fn div(self, rhs: N) -> Self {
-        Value(self.0/rhs, self.1)
+        let lhs = self.absolute();
+        Value(lhs.0/rhs, lhs.1)
     }
 }
 
@@ -307,10 +1019,193 @@ macro_rules! mul_div_primitive {
         impl Mul<Value<$t>> for $t {
             type Output = Value<Self>;
             fn mul(self, rhs: Value<$t>) -> Self::Output {
-                Value(self*rhs.0, rhs.1)
+                rhs * self
             }
         }
     )*);
 }
 
 mul_div_primitive!{f32 f64}
+
+#[test]
+fn logarithmic_test() {
+    let si = UnitSystem::<f64>::si();
+    let round = |v: Value<f64>| (v.0 * 100.).round() / 100.;
+
+    assert_eq!(round(si.as_(si.val(2., "mW").unwrap(), "dBm")), 3.01);
+    assert_eq!(round(si.as_(si.val(2., "V").unwrap(), "dBV")), 6.02);
+    assert_eq!(round(si.as_(si.val(1., "Np").unwrap(), "dB")), 8.69);
+    assert_eq!(round(si.as_(si.val(1e-7, "M").unwrap(), "pH")), 7.);
+    assert_eq!(round(si.as_(si.val(30., "dBm").unwrap(), "W")), 1.);
+
+    let gain = si.val(3., "dB").unwrap();
+    assert_eq!(gain + gain, si.val(6., "dB").unwrap());
+    assert_eq!(si.val(10., "dBm").unwrap() + gain, si.val(13., "dBm").unwrap());
+    assert_eq!(gain + si.val(10., "dBm").unwrap(), si.val(13., "dBm").unwrap());
+    assert_eq!(si.val(10., "dBm").unwrap() - si.val(4., "dBm").unwrap(), si.val(6., "dB").unwrap());
+    assert_eq!(round(si.val(6., "dBV").unwrap() + si.val(1., "Np").unwrap()), 14.69);
+    assert_eq!(si.val(10., "dBm").unwrap().checked_add(si.val(10., "dBm").unwrap()), Err(UnitError::LogarithmicAddition));
+    assert_eq!(gain.checked_add(si.val(1., "").unwrap()), Err(UnitError::LogarithmicAddition));
+
+    let power = si.val(10., "dBm").unwrap() * si.val(2., "").unwrap();
+    assert_eq!(power, si.val(0.02, "W").unwrap());
+    assert_eq!(format!("{:.2}", si.display(&si.val(20., "dBm").unwrap())), "20.00 dBm");
+}
+
+#[test]
+fn prefix_lookup_test() {
+    let si = UnitSystem::<f64>::si();
+
+    assert_eq!(si.get_unit("mm"), Some(Unit::with_factor(LENGTH, 1e-3)));
+    assert_eq!(si.get_unit("µs"), Some(Unit::with_factor(TIME, 1e-6)));
+    assert_eq!(si.get_unit("us"), si.get_unit("µs"));
+    assert_eq!(si.get_unit("GHz"), Some(Unit::with_factor(FREQUENCY, 1e9)));
+    assert_eq!(si.get_unit("kPa"), Some(Unit::with_factor(PRESSURE, 1e3)));
+    assert_eq!(si.get_unit("nF"), Some(Unit::with_factor(CAPACITANCE, 1e-9)));
+    assert_eq!(si.get_unit("kg"), Some(Unit::new(MASS)));
+    assert_eq!(si.get_unit("kkg"), None);
+    assert_eq!(si.get_unit("kmin"), None);
+    assert_eq!(si.unit_from_str("km²"), Ok(Unit::with_factor(AREA, 1e6)));
+}
+
+#[test]
+fn temperature_test() {
+    let si = UnitSystem::<f64>::si();
+    let room = si.val(300., "K").unwrap();
+
+    assert!((si.as_(room, "°C").0 - 26.85).abs() < 1e-9);
+    assert!((si.as_(si.val(-40., "°C").unwrap(), "°F").0 + 40.).abs() < 1e-9);
+    assert_eq!(si.as_(si.val(5., "Δ°C").unwrap(), "K").0, 5.);
+
+    let diff = si.val(25., "°C").unwrap() - si.val(20., "°C").unwrap();
+    assert_eq!(diff, Value(5., Unit::new(TEMPERATURE)));
+    let warmer = si.val(20., "°C").unwrap() + diff;
+    assert_eq!(warmer, si.val(25., "°C").unwrap());
+    assert_eq!(diff + si.val(20., "°C").unwrap(), warmer);
+}
+
+#[test]
+fn checked_test() {
+    let si = UnitSystem::<f64>::si();
+    let length = si.val(3., "m").unwrap();
+    let time = si.val(2., "s").unwrap();
+
+    assert_eq!(length.checked_add(time), Err(UnitError::DimensionMismatch(LENGTH, TIME)));
+    assert_eq!(length.checked_sub(si.val(1., "km").unwrap()), Ok(Value(-997., Unit::new(LENGTH))));
+    assert_eq!(si.try_as(length, "s"), Err(UnitError::DimensionMismatch(LENGTH, TIME)));
+    assert_eq!(si.try_as(length, "kmh").unwrap_err().to_string(), "No such unit `kmh', did you mean `km', `kWh'?");
+    assert_eq!(si.try_as(length, "mm"), Ok(Value(3000., Unit::with_factor(LENGTH, 1e-3))));
+    let temp = si.val(20., "°C").unwrap();
+    assert_eq!(temp.checked_add(temp), Err(UnitError::AbsoluteAddition));
+}
+
+#[test]
+#[should_panic]
+fn absolute_addition_test() {
+    let si = UnitSystem::<f64>::si();
+    let _ = si.val(20., "°C").unwrap() + si.val(20., "°C").unwrap();
+}
+
+#[test]
+fn customary_test() {
+    let us = UnitSystem::<f64>::us_customary();
+    let uk = UnitSystem::<f64>::imperial();
+    let si = UnitSystem::<f64>::si();
+    let close = |a: Value<f64>, b: f64| (a.0 - b).abs() < 1e-9 * b.abs();
+
+    assert!(close(us.as_(us.val(1., "mi").unwrap(), "ft"), 5280.));
+    assert!(close(us.as_(us.val(1., "st").unwrap(), "kg"), 6.35029318));
+    assert!(close(us.as_(us.val(1., "gal").unwrap(), "L"), 3.785411784));
+    assert!(close(uk.as_(uk.val(1., "gal").unwrap(), "L"), 4.54609));
+    assert!(close(uk.as_(uk.val(1., "gal").unwrap(), "pt"), 8.));
+    assert!(close(us.as_(us.val(1., "gal").unwrap(), "floz"), 128.));
+    assert!(close(uk.as_(uk.val(1., "ton").unwrap(), "lb"), 2240.));
+    assert!(close(us.as_(us.val(1., "psi").unwrap(), "Pa"), 6894.757293168361));
+    assert!(close(us.as_(us.val(1., "hp").unwrap(), "W"), 745.6998715822702));
+
+    let mixed = us.val(1., "ft").unwrap() + si.val(1., "m").unwrap();
+    assert_eq!(format!("{:.2}", us.display(&mixed)), "4.28 ft");
+    assert_eq!(format!("{:.1}", us.display(&us.val(0.5, "ft").unwrap())), "6.0 in");
+    assert_eq!(format!("{:.0}", us.display(&si.val(100., "°C").unwrap())), "212 °F");
+}
+
+#[test]
+fn runtime_units_test() {
+    let mut us = UnitSystem::<f64>::us_customary();
+    let name = String::from("furlong");
+    us.add_prefixable_unit(name, Unit::with_factor(LENGTH, 201.168));
+    assert_eq!(us.get_unit("kfurlong"), Some(Unit::with_factor(LENGTH, 201168.)));
+
+    assert_eq!(us.rename_unit("furlong", "fur"), Some(Unit::with_factor(LENGTH, 201.168)));
+    assert_eq!(us.get_unit("furlong"), None);
+    assert_eq!(us.get_unit("kfur"), Some(Unit::with_factor(LENGTH, 201168.)));
+
+    us.rename_unit("mi", format!("{}le", "mi"));
+    assert_eq!(format!("{}", us.display(&us.val(2., "mile").unwrap())), "2 mile");
+    assert_eq!(us.remove_unit("mile"), Some(Unit::with_factor(LENGTH, 1609.344)));
+    assert_eq!(us.remove_unit("mile"), None);
+    assert!(us.preferred[&LENGTH].iter().all(|(name, _)| name != "mile"));
+
+    assert_eq!(us.rename_unit("min", "h"), None);
+    assert_eq!(us.get_unit("h"), Some(Unit::with_factor(TIME, 3600.)));
+    assert_eq!(us.get_unit("min"), Some(Unit::with_factor(TIME, 60.)));
+
+    assert_eq!(us.remove_unit("m"), None);
+    assert_eq!(us.rename_unit("m", "metre"), Some(Unit::new(LENGTH)));
+    assert_eq!(us.base.length, "metre");
+    assert_eq!(us.val_s("2 metre").unwrap(), Value(2., Unit::new(LENGTH)));
+    assert_eq!(format!("{}", us.display(&Value(2., Unit::new(AREA)))), "2 metre²");
+    let mut angles = UnitSystem::<f64>::si_with_angles();
+    angles.rename_unit("rad", "radian");
+    assert_eq!(angles.base.angle.as_deref(), Some("radian"));
+    assert_eq!(angles.remove_unit("radian"), None);
+}
+
+#[test]
+fn alias_test() {
+    let si = UnitSystem::<f64>::si();
+    assert_eq!(si.get_unit("metre"), si.get_unit("m"));
+    assert_eq!(si.get_unit("meters"), si.get_unit("m"));
+    assert_eq!(si.get_unit("ohm"), si.get_unit("Ω"));
+    assert_eq!(si.get_unit("litres"), si.get_unit("L"));
+    assert_eq!(si.get_unit("kilograms"), si.get_unit("kg"));
+    assert_eq!(si.get_unit("milligrams"), si.get_unit("mg"));
+    assert_eq!(si.get_unit("kilometres"), si.get_unit("km"));
+    assert_eq!(si.get_unit("microseconds"), si.get_unit("µs"));
+    assert_eq!(si.get_unit("kmetres"), None);
+    assert_eq!(si.get_unit("kilom"), None);
+    let mut stray = UnitSystem::<f64>::si();
+    stray.prefixable.insert("nonexistent".into());
+    assert_eq!(stray.get_unit("knonexistent"), None);
+    assert_eq!(si.val_s("3 seconds").unwrap(), si.val(3., "s").unwrap());
+    assert_eq!(si.long_name("Hz", true), Some("hertz"));
+
+    let mut us = UnitSystem::<f64>::us_customary();
+    assert_eq!(us.get_unit("feet"), us.get_unit("ft"));
+    us.rename_unit("ft", "foot");
+    assert_eq!(us.get_unit("feet"), us.get_unit("foot"));
+    assert_eq!(us.long_name("foot", true), Some("feet"));
+    us.remove_unit("foot");
+    assert_eq!(us.get_unit("feet"), None);
+}
+
+#[test]
+fn angle_test() {
+    let si = UnitSystem::<f64>::si();
+    assert_eq!(si.unit_from_str("rad/s").unwrap().dimension, FREQUENCY);
+    assert!((si.as_(si.val_s("1 turn").unwrap(), "deg").0 - 360.).abs() < 1e-9);
+    assert!((si.as_(si.val_s("90 °").unwrap(), "grad").0 - 100.).abs() < 1e-9);
+    assert!((si.as_(si.val_s("1 deg").unwrap(), "arcsec").0 - 3600.).abs() < 1e-9);
+    assert_eq!(si.display(&si.val_s("2 rad").unwrap()).to_string(), "2");
+
+    let angles = UnitSystem::<f64>::si_with_angles();
+    assert_eq!(angles.unit_from_str("rad/s").unwrap().dimension, ANGULAR_VELOCITY);
+    assert_eq!(angles.get_unit("sr").unwrap().dimension, SOLID_ANGLE);
+    assert_eq!(angles.get_unit("arcmin").unwrap().dimension, ANGLE);
+    assert_eq!(angles.angle(), ANGLE);
+    assert_eq!(si.angle(), NUL);
+    assert_eq!(si.base.angle, None);
+    assert_eq!(UnitSystem::<f64>::new_base_with_cap(SI, 0).get_unit("rad"), None);
+    assert!(angles.try_as(angles.val_s("2 rad/s").unwrap(), "Hz").is_err());
+    assert_eq!(angles.display(&angles.val_s("2 rad/s").unwrap()).to_string(), "2 rad·s⁻¹");
+}